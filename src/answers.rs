@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::fs::{read_to_string, write};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+use crate::puzzle::input::{embedded_file, input_file};
+use crate::puzzle::{Answer, Part};
+
+const ANSWERS_FILE: &str = "answers.toml";
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Io(PathBuf, io::Error),
+    Syntax(PathBuf, String),
+    InvalidKey(String),
+    InvalidAnswer(String),
+}
+
+impl Display for AnswersError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnswersError::Io(path, error) => {
+                write!(f, "Could not access answers {}: {}", path.display(), error)
+            }
+            AnswersError::Syntax(path, error) => {
+                write!(f, "Invalid answers file {}: {}", path.display(), error)
+            }
+            AnswersError::InvalidKey(key) => {
+                write!(f, "Invalid key (e.g. [day14] part2 = 42): {}", key)
+            }
+            AnswersError::InvalidAnswer(key) => {
+                write!(f, "Answer must be an integer or a string: {}", key)
+            }
+        }
+    }
+}
+
+pub fn default_answers_path(year: u16) -> PathBuf {
+    input_file(year, ANSWERS_FILE)
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Check<'a> {
+    Match,
+    Mismatch(&'a Answer),
+    Unrecorded,
+}
+
+#[derive(PartialEq, Debug, Default)]
+pub struct Answers(BTreeMap<(u8, u8), Answer>);
+
+impl Answers {
+    pub fn load(path: &Path) -> Result<Self, AnswersError> {
+        let content = read_to_string(path).map_err(|error| AnswersError::Io(path.into(), error))?;
+        Answers::parse(path, &content)
+    }
+
+    /// Falls back to the embedded answers when the file of the year is not on disk
+    pub fn load_default(year: u16) -> Result<Self, AnswersError> {
+        let path = default_answers_path(year);
+        match read_to_string(&path) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                embedded_file(&format!("{}/{}", year, ANSWERS_FILE))
+                    .map(String::from)
+                    .ok_or(error)
+            }
+            content => content,
+        }
+        .map_err(|error| AnswersError::Io(path.clone(), error))
+        .and_then(|content| Answers::parse(&path, &content))
+    }
+
+    fn parse(path: &Path, content: &str) -> Result<Self, AnswersError> {
+        content
+            .parse::<Table>()
+            .map_err(|error| AnswersError::Syntax(path.into(), error.message().into()))
+            .and_then(|table| Answers::from_table(&table))
+    }
+
+    pub fn load_or_default(path: &Path) -> Result<Self, AnswersError> {
+        match Answers::load(path) {
+            Err(AnswersError::Io(_, error)) if error.kind() == io::ErrorKind::NotFound => {
+                Ok(Answers::default())
+            }
+            answers => answers,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AnswersError> {
+        write(path, self.to_string()).map_err(|error| AnswersError::Io(path.into(), error))
+    }
+
+    fn from_table(table: &Table) -> Result<Self, AnswersError> {
+        let mut answers = Answers::default();
+        for (day_key, parts) in table {
+            let day = parse_key(day_key, "day")
+                .ok_or_else(|| AnswersError::InvalidKey(day_key.clone()))?;
+            let parts = parts
+                .as_table()
+                .ok_or_else(|| AnswersError::InvalidKey(day_key.clone()))?;
+            for (part_key, value) in parts {
+                let key = format!("{}.{}", day_key, part_key);
+                let part = parse_key(part_key, "part")
+                    .and_then(Part::from_number)
+                    .ok_or_else(|| AnswersError::InvalidKey(key.clone()))?;
+                let answer = match value {
+                    Value::Integer(value) => Answer::from(*value),
+                    Value::String(value) => value
+                        .parse::<i128>()
+                        .map(Answer::Integer)
+                        .unwrap_or_else(|_| Answer::Text(value.clone())),
+                    _ => return Err(AnswersError::InvalidAnswer(key)),
+                };
+                answers.record(day, part, answer);
+            }
+        }
+        Ok(answers)
+    }
+
+    pub fn record(&mut self, day: u8, part: Part, answer: Answer) {
+        self.0.insert((day, part.number()), answer);
+    }
+
+    pub fn check(&self, day: u8, part: Part, actual: &Answer) -> Check<'_> {
+        match self.0.get(&(day, part.number())) {
+            Some(expected) if expected.to_string() == actual.to_string() => Check::Match,
+            Some(expected) => Check::Mismatch(expected),
+            None => Check::Unrecorded,
+        }
+    }
+}
+
+fn parse_key<T: std::str::FromStr>(key: &str, prefix: &str) -> Option<T> {
+    key.strip_prefix(prefix)?.parse().ok()
+}
+
+impl Display for Answers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut current_day = None;
+        for ((day, part), answer) in &self.0 {
+            if current_day != Some(*day) {
+                if current_day.is_some() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[day{}]", day)?;
+                current_day = Some(*day);
+            }
+            let value = match answer {
+                Answer::Integer(value) => i64::try_from(*value)
+                    .map(Value::from)
+                    .unwrap_or_else(|_| Value::from(value.to_string())),
+                Answer::Text(text) => Value::from(text.as_str()),
+            };
+            writeln!(f, "part{} = {}", part, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod answers_should {
+    use super::*;
+
+    fn parse(content: &str) -> Result<Answers, AnswersError> {
+        Answers::from_table(&content.parse().unwrap())
+    }
+
+    #[test]
+    fn parse_integer_and_text_answers() {
+        let answers = parse("[day13]\npart1 = 2215\npart2 = \"abc\"\n").unwrap();
+
+        assert_eq!(
+            answers.check(13, Part::One, &Answer::from(2215u32)),
+            Check::Match
+        );
+        assert_eq!(
+            answers.check(13, Part::Two, &Answer::from(String::from("abc"))),
+            Check::Match
+        );
+    }
+
+    #[test]
+    fn report_the_expected_answer_on_a_mismatch() {
+        let answers = parse("[day1]\npart1 = 12\n").unwrap();
+
+        assert_eq!(
+            answers.check(1, Part::One, &Answer::from(13u32)),
+            Check::Mismatch(&Answer::from(12u32))
+        );
+    }
+
+    #[test]
+    fn report_unrecorded_answers() {
+        let answers = parse("[day1]\npart1 = 12\n").unwrap();
+
+        assert_eq!(
+            answers.check(1, Part::Two, &Answer::from(13u32)),
+            Check::Unrecorded
+        );
+    }
+
+    #[test]
+    fn reject_an_unknown_part() {
+        assert!(matches!(
+            parse("[day1]\npart3 = 12\n"),
+            Err(AnswersError::InvalidKey(key)) if key == "day1.part3"
+        ));
+    }
+
+    #[test]
+    fn write_days_in_numeric_order() {
+        let mut answers = Answers::default();
+        answers.record(10, Part::One, Answer::from(2059u32));
+        answers.record(2, Part::Two, Answer::from(String::from("abc")));
+        answers.record(2, Part::One, Answer::Integer(i128::MAX));
+
+        assert_eq!(
+            answers.to_string(),
+            "[day2]\n\
+             part1 = \"170141183460469231731687303715884105727\"\n\
+             part2 = \"abc\"\n\
+             \n\
+             [day10]\n\
+             part1 = 2059\n"
+        );
+    }
+
+    #[test]
+    fn read_back_what_it_writes() {
+        let mut answers = Answers::default();
+        answers.record(2, Part::One, Answer::Integer(i128::MAX));
+        answers.record(10, Part::Two, Answer::from(86812553324672u64));
+
+        assert_eq!(parse(&answers.to_string()).unwrap(), answers);
+    }
+}
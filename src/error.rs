@@ -0,0 +1,99 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Error {
+    InvalidInput {
+        day: u8,
+        line: Option<usize>,
+        text: String,
+        reason: String,
+    },
+    UnknownDay {
+        year: u16,
+        day: u8,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidInput {
+                day,
+                line,
+                text,
+                reason,
+            } => {
+                write!(f, "Day {}", day)?;
+                if let Some(line) = line {
+                    write!(f, ", line {}", line)?;
+                }
+                write!(f, ": {}", reason)?;
+                if !text.is_empty() {
+                    write!(f, ": {:?}", text)?;
+                }
+                Ok(())
+            }
+            Error::UnknownDay { year, day } => write!(f, "No puzzle for day {} of {}", day, year),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// An invalid piece of input, located by the day once the whole input is parsed
+#[derive(Clone, PartialEq, Debug)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub text: String,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(text: &str, reason: impl Into<String>) -> Self {
+        Self {
+            line: None,
+            text: text.into(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn at_line(self, line: usize) -> Self {
+        Self {
+            line: Some(line),
+            ..self
+        }
+    }
+
+    pub fn in_day(self, day: u8) -> Error {
+        Error::InvalidInput {
+            day,
+            line: self.line,
+            text: self.text,
+            reason: self.reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod error_should {
+    use super::*;
+
+    #[test]
+    fn display_the_day_line_and_offending_text() {
+        let error = ParseError::new("nop x", "invalid argument")
+            .at_line(3)
+            .in_day(8);
+
+        assert_eq!(
+            error.to_string(),
+            r#"Day 8, line 3: invalid argument: "nop x""#
+        );
+    }
+
+    #[test]
+    fn display_only_the_reason_when_there_is_no_line_nor_text() {
+        let error = ParseError::new("", "missing bus line").in_day(13);
+
+        assert_eq!(error.to_string(), "Day 13: missing bus line");
+    }
+}
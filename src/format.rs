@@ -0,0 +1,94 @@
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use advent2020::puzzle::{Answer, Execution, Puzzle};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct Record<'a> {
+    year: u16,
+    day: u8,
+    part: u8,
+    label: &'static str,
+    answer: &'a Answer,
+    /// In nanoseconds
+    duration: u128,
+}
+
+pub fn records<'a>(puzzle: &dyn Puzzle, execution: &'a Execution) -> Vec<Record<'a>> {
+    execution
+        .results
+        .iter()
+        .map(|result| Record {
+            year: puzzle.year(),
+            day: puzzle.day(),
+            part: result.part.number(),
+            label: puzzle.label(result.part),
+            answer: &result.answer,
+            duration: result.duration.as_nanos(),
+        })
+        .collect()
+}
+
+pub fn to_json(records: &[Record]) -> String {
+    serde_json::to_string(records).expect("Records are always serializable")
+}
+
+#[cfg(test)]
+mod to_json_should {
+    use super::*;
+
+    #[test]
+    fn serialize_integer_answers_as_numbers() {
+        let answer = Answer::from(1058443396696792u64);
+        let records = [Record {
+            year: 2020,
+            day: 13,
+            part: 2,
+            label: "Earliest timestamp",
+            answer: &answer,
+            duration: 1_400,
+        }];
+
+        assert_eq!(
+            to_json(&records),
+            r#"[{"year":2020,"day":13,"part":2,"label":"Earliest timestamp","answer":1058443396696792,"duration":1400}]"#
+        );
+    }
+
+    #[test]
+    fn serialize_text_answers_as_strings() {
+        let answer = Answer::from(String::from("abc"));
+        let records = [Record {
+            year: 2020,
+            day: 1,
+            part: 1,
+            label: "Label",
+            answer: &answer,
+            duration: 0,
+        }];
+
+        assert_eq!(
+            to_json(&records),
+            r#"[{"year":2020,"day":1,"part":1,"label":"Label","answer":"abc","duration":0}]"#
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Entry points for the targets of the `fuzz` crate, which only see the public API. Each one feeds
+//! arbitrary bytes to a parser, invalid UTF-8 sequences being replaced, some then solving what it
+//! accepts, and must never panic.
+
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
+use crate::puzzle::day11::WaitingArea;
+use crate::puzzle::day12::Action;
+use crate::puzzle::day14::{self, Day14};
+use crate::puzzle::day16::ColumnRule;
+use crate::puzzle::day17::{ConwayCubes, Day17};
+use crate::puzzle::day4::{is_fully_valid, parse_passports};
+use crate::puzzle::day5::BoardingPass;
+use crate::puzzle::day8::parse_instruction;
+use crate::puzzle::Solution;
+
+/// Beyond a few cubes, six cycles take too long for a fuzzer
+const MAX_CUBES_INPUT_SIZE: usize = 32;
+
+fn text(data: &[u8]) -> Cow<'_, str> {
+    String::from_utf8_lossy(data)
+}
+
+/// Also validates the fields of the parsed passports
+pub fn passports(data: &[u8]) {
+    if let Ok(passports) = parse_passports(&text(data)) {
+        for passport in &passports {
+            is_fully_valid(passport);
+        }
+    }
+}
+
+pub fn boarding_pass(data: &[u8]) {
+    let _ = BoardingPass::try_from(text(data).as_ref());
+}
+
+pub fn boot_instruction(data: &[u8]) {
+    let _ = parse_instruction(&text(data));
+}
+
+pub fn waiting_area(data: &[u8]) {
+    let _ = WaitingArea::try_from(text(data).as_ref());
+}
+
+pub fn navigation_action(data: &[u8]) {
+    let _ = Action::try_from(text(data).as_ref());
+}
+
+pub fn initialization_instruction(data: &[u8]) {
+    let _ = day14::InitializationInstruction::try_from(text(data).as_ref());
+}
+
+/// The instructions of the second part, whose masks are decoded differently
+pub fn floating_initialization_instruction(data: &[u8]) {
+    let _ = day14::part2::InitializationInstruction::try_from(text(data).as_ref());
+}
+
+/// Runs the decoder of the second part on the accepted programs
+pub fn floating_initialization_program(data: &[u8]) {
+    if let Ok(program) = Day14.parse(&text(data)) {
+        let _ = Day14.part2(&program);
+    }
+}
+
+pub fn column_rule(data: &[u8]) {
+    let _ = ColumnRule::try_from(text(data).as_ref());
+}
+
+/// Solves the first part of small slices, and plays the first cycles in four dimensions, whose
+/// bounding box grows much faster
+pub fn conway_cubes(data: &[u8]) {
+    if data.len() > MAX_CUBES_INPUT_SIZE {
+        return;
+    }
+    if let Ok(slice) = Day17.parse(&text(data)) {
+        let _ = Day17.part1(&slice);
+        ConwayCubes::from_slice(&slice, 4).next_cycle().next_cycle();
+    }
+}
+
+#[cfg(test)]
+mod targets_should {
+    use super::*;
+
+    const TARGETS: [fn(&[u8]); 10] = [
+        passports,
+        boarding_pass,
+        boot_instruction,
+        waiting_area,
+        navigation_action,
+        initialization_instruction,
+        floating_initialization_instruction,
+        floating_initialization_program,
+        column_rule,
+        conway_cubes,
+    ];
+
+    #[test]
+    fn not_panic_on_multi_byte_characters() {
+        let inputs: [&[u8]; 9] = [
+            "é".as_bytes(),
+            "FBFBBFFRLé".as_bytes(),
+            "FBFBBFéRLR".as_bytes(),
+            "€€€€€€€€€€".as_bytes(),
+            "hgt:1€\nbyr:é".as_bytes(),
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXé".as_bytes(),
+            "Fé\nL90\nR٩٠".as_bytes(),
+            "class: 1-3 or 5-٧".as_bytes(),
+            &[b'#', 0xff, b'.', 0xc3, b'\n', b'L'],
+        ];
+
+        for target in TARGETS.iter() {
+            for input in inputs.iter() {
+                target(input);
+            }
+        }
+    }
+
+    #[test]
+    fn not_panic_on_numbers_out_of_range() {
+        let inputs = [
+            "acc +99999999999999999999",
+            "R-9223372036854775808",
+            "F9223372036854775808",
+            "mem[99999999999999999999] = 1",
+            "mem[1] = 99999999999999999999",
+            "row: 0-4294967296 or 1-2",
+            "byr:99999999999 iyr:-1 eyr:+ hgt:99999999999cm hcl:#123abc ecl:oth pid:000000000",
+        ];
+
+        for target in TARGETS.iter() {
+            for input in inputs.iter() {
+                target(input.as_bytes());
+            }
+        }
+    }
+
+    #[test]
+    fn not_panic_when_solving_accepted_inputs() {
+        let mask = format!("mask = {}X", "0".repeat(35));
+        let program = format!("{}\nmem[1] = 68719476735\nmem[2] = 68719476735", mask);
+
+        floating_initialization_program(program.as_bytes());
+        conway_cubes(b".#.\n..#\n###");
+    }
+}
@@ -0,0 +1,258 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+/// A position or a displacement on an integer plane, with y going south like the rows of a grid
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Vector {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Vector {
+    pub const ORIGIN: Vector = Vector { x: 0, y: 0 };
+
+    pub const fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+
+    /// Rotates around the origin, clockwise for a positive number of quarter turns
+    pub fn rotate(self, quarter_turns: isize) -> Self {
+        match quarter_turns.rem_euclid(4) {
+            0 => self,
+            1 => Self::new(-self.y, self.x),
+            2 => -self,
+            _ => Self::new(self.y, -self.x),
+        }
+    }
+
+    pub fn manhattan_length(self) -> usize {
+        self.x.unsigned_abs() + self.y.unsigned_abs()
+    }
+
+    pub fn manhattan_distance(self, other: Vector) -> usize {
+        (other - self).manhattan_length()
+    }
+
+    pub fn chebyshev_length(self) -> usize {
+        self.x.unsigned_abs().max(self.y.unsigned_abs())
+    }
+
+    pub fn chebyshev_distance(self, other: Vector) -> usize {
+        (other - self).chebyshev_length()
+    }
+
+    /// None when a coordinate overflows
+    pub fn checked_add(self, other: Vector) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_add(other.x)?,
+            self.y.checked_add(other.y)?,
+        ))
+    }
+
+    pub fn checked_mul(self, factor: isize) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_mul(factor)?,
+            self.y.checked_mul(factor)?,
+        ))
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Self::Output {
+        Vector::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, other: Vector) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Self::Output {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Self::Output {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<isize> for Vector {
+    type Output = Vector;
+
+    fn mul(self, factor: isize) -> Self::Output {
+        Vector::new(self.x * factor, self.y * factor)
+    }
+}
+
+/// The four cardinal directions, in clockwise order
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// The unit step towards the direction
+    pub fn vector(self) -> Vector {
+        match self {
+            Direction::North => Vector::new(0, -1),
+            Direction::East => Vector::new(1, 0),
+            Direction::South => Vector::new(0, 1),
+            Direction::West => Vector::new(-1, 0),
+        }
+    }
+
+    /// Turns clockwise for a positive number of quarter turns
+    pub fn turn(self, quarter_turns: isize) -> Self {
+        Direction::ALL[(self as isize + quarter_turns).rem_euclid(4) as usize]
+    }
+}
+
+/// The eight points of the compass, in clockwise order
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CompassDirection {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl CompassDirection {
+    pub const ALL: [CompassDirection; 8] = [
+        CompassDirection::North,
+        CompassDirection::NorthEast,
+        CompassDirection::East,
+        CompassDirection::SouthEast,
+        CompassDirection::South,
+        CompassDirection::SouthWest,
+        CompassDirection::West,
+        CompassDirection::NorthWest,
+    ];
+
+    /// The step towards the direction, diagonal ones moving along both axes
+    pub fn vector(self) -> Vector {
+        match self {
+            CompassDirection::North => Vector::new(0, -1),
+            CompassDirection::NorthEast => Vector::new(1, -1),
+            CompassDirection::East => Vector::new(1, 0),
+            CompassDirection::SouthEast => Vector::new(1, 1),
+            CompassDirection::South => Vector::new(0, 1),
+            CompassDirection::SouthWest => Vector::new(-1, 1),
+            CompassDirection::West => Vector::new(-1, 0),
+            CompassDirection::NorthWest => Vector::new(-1, -1),
+        }
+    }
+
+    /// Turns clockwise for a positive number of quarter turns
+    pub fn turn(self, quarter_turns: isize) -> Self {
+        CompassDirection::ALL[(self as isize + 2 * quarter_turns).rem_euclid(8) as usize]
+    }
+}
+
+impl From<Direction> for CompassDirection {
+    fn from(direction: Direction) -> Self {
+        CompassDirection::ALL[2 * direction as usize]
+    }
+}
+
+#[cfg(test)]
+mod vector_should {
+    use super::*;
+
+    #[test]
+    fn rotate_clockwise_by_quarter_turns() {
+        let vector = Vector::new(10, 4);
+
+        assert_eq!(vector.rotate(1), Vector::new(-4, 10));
+        assert_eq!(vector.rotate(2), Vector::new(-10, -4));
+        assert_eq!(vector.rotate(-1), Vector::new(4, -10));
+        assert_eq!(vector.rotate(-4), vector);
+    }
+
+    #[test]
+    fn measure_manhattan_and_chebyshev_distances() {
+        let from = Vector::new(-2, 3);
+        let to = Vector::new(4, 1);
+
+        assert_eq!(from.manhattan_distance(to), 8);
+        assert_eq!(from.chebyshev_distance(to), 6);
+        assert_eq!(Vector::new(17, -8).manhattan_length(), 25);
+    }
+
+    #[test]
+    fn combine_with_arithmetic_operators() {
+        let mut position = Vector::ORIGIN;
+        position += Direction::East.vector() * 10;
+
+        assert_eq!(position - Vector::new(3, -1), Vector::new(7, 1));
+    }
+
+    #[test]
+    fn detect_an_overflow_in_checked_operations() {
+        let far = Vector::new(isize::MAX / 2 + 1, 1);
+
+        assert_eq!(far.checked_mul(-2), Some(Vector::new(isize::MIN, -2)));
+        assert_eq!(far.checked_mul(2), None);
+        assert_eq!(far.checked_add(Vector::new(0, isize::MAX)), None);
+        assert_eq!(far.checked_add(-far), Some(Vector::ORIGIN));
+    }
+}
+
+#[cfg(test)]
+mod direction_should {
+    use super::*;
+
+    #[test]
+    fn turn_by_quarter_turns() {
+        assert_eq!(Direction::East.turn(1), Direction::South);
+        assert_eq!(Direction::East.turn(3), Direction::North);
+        assert_eq!(Direction::East.turn(-1), Direction::North);
+        assert_eq!(Direction::North.turn(-6), Direction::South);
+    }
+
+    #[test]
+    fn rotate_its_vector_like_a_vector() {
+        for direction in Direction::ALL.iter() {
+            assert_eq!(direction.turn(1).vector(), direction.vector().rotate(1));
+        }
+    }
+
+    #[test]
+    fn turn_compass_directions_by_quarter_turns() {
+        assert_eq!(
+            CompassDirection::NorthEast.turn(1),
+            CompassDirection::SouthEast
+        );
+        assert_eq!(
+            CompassDirection::NorthWest.turn(-1),
+            CompassDirection::SouthWest
+        );
+        assert_eq!(
+            CompassDirection::from(Direction::West),
+            CompassDirection::West
+        );
+    }
+}
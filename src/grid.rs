@@ -0,0 +1,247 @@
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+use crate::error::ParseError;
+use crate::geometry::{CompassDirection, Direction, Vector};
+use crate::puzzle::input::parse_lines;
+
+/// A cell written as a single character
+pub trait Cell: Copy + PartialEq + 'static {
+    const CHARS: &'static [(char, Self)];
+
+    fn from_char(c: char) -> Option<Self> {
+        Self::CHARS
+            .iter()
+            .find(|(character, _)| *character == c)
+            .map(|(_, cell)| *cell)
+    }
+
+    fn to_char(&self) -> char {
+        Self::CHARS
+            .iter()
+            .find(|(_, cell)| cell == self)
+            .map_or('?', |(character, _)| *character)
+    }
+}
+
+/// Cells stored row after row, addressed by their position from the top left corner
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, position: Vector) -> Option<&T> {
+        let x = usize::try_from(position.x)
+            .ok()
+            .filter(|x| *x < self.width)?;
+        let y = usize::try_from(position.y)
+            .ok()
+            .filter(|y| *y < self.height)?;
+        self.cells.get(y * self.width + x)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// A grid of the same size, each cell computed from its position and its current value
+    pub fn map<U>(&self, f: impl Fn(Vector, &T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self
+                .cells
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    let position =
+                        Vector::new((i % self.width) as isize, (i / self.width) as isize);
+                    f(position, cell)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn neighbours4(&self, position: Vector) -> impl Iterator<Item = &T> {
+        Direction::ALL
+            .iter()
+            .filter_map(move |direction| self.get(position + direction.vector()))
+    }
+
+    pub fn neighbours8(&self, position: Vector) -> impl Iterator<Item = &T> {
+        CompassDirection::ALL
+            .iter()
+            .filter_map(move |direction| self.get(position + direction.vector()))
+    }
+
+    /// The cells met from the position, excluded, until the edge
+    pub fn ray(&self, position: Vector, direction: CompassDirection) -> impl Iterator<Item = &T> {
+        (1..).map_while(move |distance| self.get(position + direction.vector() * distance))
+    }
+}
+
+impl<T: Cell> Grid<T> {
+    /// Parses non-empty lines as rows, which must all have the same width
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut width = None;
+        let rows = parse_lines(input, |line| {
+            let row = line
+                .chars()
+                .map(|c| T::from_char(c).ok_or_else(|| ParseError::new(line, expected::<T>())))
+                .collect::<Result<Vec<_>, _>>()?;
+            match width {
+                Some(width) if width != row.len() => {
+                    Err(ParseError::new(line, "expected rows of the same width"))
+                }
+                _ => {
+                    width = Some(row.len());
+                    Ok(row)
+                }
+            }
+        })?;
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+}
+
+fn expected<T: Cell>() -> String {
+    let chars = T::CHARS
+        .iter()
+        .map(|(c, _)| format!("'{}'", c))
+        .collect::<Vec<_>>();
+    match chars.split_last() {
+        Some((last, [])) => format!("expected only {}", last),
+        Some((last, others)) => format!("expected only {} and {}", others.join(", "), last),
+        None => "expected no cell".into(),
+    }
+}
+
+impl<T: Cell> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                write!(f, "{}", cell.to_char())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod grid_should {
+    use super::*;
+
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    enum Pixel {
+        Off,
+        On,
+    }
+
+    impl Cell for Pixel {
+        const CHARS: &'static [(char, Self)] = &[('.', Pixel::Off), ('#', Pixel::On)];
+    }
+
+    fn grid(input: &str) -> Grid<Pixel> {
+        Grid::parse(input).unwrap()
+    }
+
+    #[test]
+    fn parse_rows_of_cells() {
+        let grid = grid("#..\n.#.\n");
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(Vector::new(1, 1)), Some(&Pixel::On));
+        assert_eq!(grid.get(Vector::new(2, 1)), Some(&Pixel::Off));
+    }
+
+    #[test]
+    fn return_nothing_out_of_bounds() {
+        let grid = grid("#.\n.#");
+
+        assert_eq!(grid.get(Vector::new(-1, 0)), None);
+        assert_eq!(grid.get(Vector::new(2, 0)), None);
+        assert_eq!(grid.get(Vector::new(0, 2)), None);
+    }
+
+    #[test]
+    fn fail_on_an_unknown_character() {
+        assert_eq!(
+            Grid::<Pixel>::parse("..\n.x"),
+            Err(ParseError::new(".x", "expected only '.' and '#'").at_line(2))
+        );
+    }
+
+    #[test]
+    fn fail_on_rows_of_different_widths() {
+        assert_eq!(
+            Grid::<Pixel>::parse("..\n...\n"),
+            Err(ParseError::new("...", "expected rows of the same width").at_line(2))
+        );
+    }
+
+    #[test]
+    fn return_the_neighbours_within_bounds() {
+        let grid = grid("##.\n#..\n..#");
+        let center = Vector::new(1, 1);
+
+        assert_eq!(grid.neighbours4(Vector::ORIGIN).count(), 2);
+        assert_eq!(grid.neighbours8(Vector::ORIGIN).count(), 3);
+        assert_eq!(
+            grid.neighbours8(center)
+                .filter(|p| **p == Pixel::On)
+                .count(),
+            4
+        );
+        assert_eq!(
+            grid.neighbours4(center)
+                .filter(|p| **p == Pixel::On)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn cast_a_ray_until_the_edge() {
+        let grid = grid("#...\n....\n..#.\n...#");
+
+        assert_eq!(
+            grid.ray(Vector::ORIGIN, CompassDirection::SouthEast)
+                .collect::<Vec<_>>(),
+            vec![&Pixel::Off, &Pixel::On, &Pixel::On]
+        );
+        assert_eq!(grid.ray(Vector::ORIGIN, CompassDirection::West).count(), 0);
+    }
+
+    #[test]
+    fn map_the_cells_with_their_position() {
+        let grid = grid("..\n..").map(|position, _| {
+            if position.x == position.y {
+                Pixel::On
+            } else {
+                Pixel::Off
+            }
+        });
+
+        assert_eq!(grid.to_string(), "#.\n.#\n");
+    }
+
+    #[test]
+    fn display_the_rows() {
+        assert_eq!(grid("\n#.#\n.#.\n").to_string(), "#.#\n.#.\n");
+    }
+}
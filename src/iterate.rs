@@ -0,0 +1,161 @@
+//! Drivers applying a `State -> State` step until the states stop changing or repeat.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    /// The state maps to itself
+    Fixpoint,
+    /// The state comes back every `period` steps
+    Cycle { period: usize },
+    /// The maximum number of steps was taken first
+    MaxSteps,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Iteration<S> {
+    pub state: S,
+    /// The number of steps taken to first reach the state
+    pub steps: usize,
+    pub outcome: Outcome,
+}
+
+/// Steps until a state maps to itself, which never happens for a longer cycle unless the number
+/// of steps is bounded
+pub fn fixpoint<S: PartialEq>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    max_steps: Option<usize>,
+) -> Iteration<S> {
+    let mut state = initial;
+    let mut steps = 0;
+    loop {
+        if max_steps.is_some_and(|max_steps| steps >= max_steps) {
+            return Iteration {
+                state,
+                steps,
+                outcome: Outcome::MaxSteps,
+            };
+        }
+        let next = step(&state);
+        if next == state {
+            return Iteration {
+                state,
+                steps,
+                outcome: Outcome::Fixpoint,
+            };
+        }
+        state = next;
+        steps += 1;
+    }
+}
+
+/// Steps until a state comes back, remembering every state met so far
+pub fn find_cycle<S: Clone + Eq + Hash>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    max_steps: Option<usize>,
+) -> Iteration<S> {
+    let mut seen = HashMap::new();
+    let mut state = initial;
+    let mut steps = 0;
+    loop {
+        if let Some(first_seen) = seen.get(&state) {
+            let outcome = match steps - first_seen {
+                1 => Outcome::Fixpoint,
+                period => Outcome::Cycle { period },
+            };
+            return Iteration {
+                state,
+                steps: *first_seen,
+                outcome,
+            };
+        }
+        if max_steps.is_some_and(|max_steps| steps >= max_steps) {
+            return Iteration {
+                state,
+                steps,
+                outcome: Outcome::MaxSteps,
+            };
+        }
+        let next = step(&state);
+        seen.insert(state, steps);
+        state = next;
+        steps += 1;
+    }
+}
+
+#[cfg(test)]
+mod fixpoint_should {
+    use super::*;
+
+    #[test]
+    fn stop_on_the_first_state_mapping_to_itself() {
+        let iteration = fixpoint(100, |n| n / 2 + n % 2, None);
+
+        assert_eq!(
+            iteration,
+            Iteration {
+                state: 1,
+                steps: 7,
+                outcome: Outcome::Fixpoint
+            }
+        );
+    }
+
+    #[test]
+    fn stop_after_the_maximum_number_of_steps() {
+        let iteration = fixpoint(0, |n| n + 1, Some(6));
+
+        assert_eq!(
+            iteration,
+            Iteration {
+                state: 6,
+                steps: 6,
+                outcome: Outcome::MaxSteps
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod find_cycle_should {
+    use super::*;
+
+    #[test]
+    fn report_the_period_and_the_start_of_a_cycle() {
+        let iteration = find_cycle(0, |n| if *n < 5 { n + 1 } else { 2 }, None);
+
+        assert_eq!(
+            iteration,
+            Iteration {
+                state: 2,
+                steps: 2,
+                outcome: Outcome::Cycle { period: 4 }
+            }
+        );
+    }
+
+    #[test]
+    fn report_a_fixpoint_as_a_cycle_of_one_step() {
+        let iteration = find_cycle(9, |n| n / 3, None);
+
+        assert_eq!(
+            iteration,
+            Iteration {
+                state: 0,
+                steps: 3,
+                outcome: Outcome::Fixpoint
+            }
+        );
+    }
+
+    #[test]
+    fn stop_after_the_maximum_number_of_steps() {
+        let iteration = find_cycle(1u64, |n| n * 3, Some(4));
+
+        assert_eq!(iteration.state, 81);
+        assert_eq!(iteration.outcome, Outcome::MaxSteps);
+    }
+}
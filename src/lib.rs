@@ -0,0 +1,43 @@
+//! Solvers for the [Advent of Code 2020](https://adventofcode.com/2020) puzzles.
+//!
+//! Each day is a [`Solution`](puzzle::Solution): parse the input once, then solve each part
+//! from the parsed model.
+//!
+//! ```
+//! use advent2020::puzzle::day1::Day1;
+//! use advent2020::puzzle::{Answer, Solution};
+//!
+//! # fn main() -> Result<(), advent2020::error::ParseError> {
+//! let expenses = Day1.parse("1721\n979\n366\n299\n675\n1456")?;
+//! assert_eq!(Day1.part1(&expenses)?, Answer::Integer(514579));
+//! assert_eq!(Day1.part2(&expenses)?, Answer::Integer(241861950));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The registered days are also available as [`Puzzle`](puzzle::Puzzle) trait objects through
+//! [`PUZZLES`](puzzle::PUZZLES) and [`find_puzzle`](puzzle::find_puzzle), by year and day, or
+//! directly with [`solve`](puzzle::solve), which reports an invalid input as an
+//! [`Error`](error::Error). Days are of [`DEFAULT_YEAR`](puzzle::DEFAULT_YEAR) unless their
+//! solution says otherwise.
+//!
+//! Days can also [`generate`](puzzle::Puzzle::generate) random inputs of any size from a seed, to
+//! stress their solvers beyond the size of the personal inputs.
+//!
+//! The parsers are fuzzed through the `fuzz` entry points, by the targets of the `fuzz` crate
+//! (e.g. `cargo +nightly fuzz run day5_boarding_pass`). Those entry points are only built with the
+//! `fuzzing` feature, which the `fuzz` crate enables.
+
+pub mod answers;
+pub mod error;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
+pub mod geometry;
+pub mod grid;
+pub mod iterate;
+pub mod math;
+pub mod puzzle;
+pub mod random;
+pub mod repl;
+pub mod selector;
+pub mod trace;
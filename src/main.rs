@@ -1,40 +1,597 @@
 use std::env;
-use std::io::Result;
+use std::fmt::Display;
+use std::fs::write;
+use std::io::{stdin, stdout, BufRead, Write};
+use std::net::{Ipv4Addr, TcpListener};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::Instant;
 
-mod puzzle;
+use advent2020::answers::{default_answers_path, Answers, Check};
+use advent2020::puzzle::input::InputSource;
+use advent2020::puzzle::{puzzles_of, years, Execution, Part, Puzzle};
+use advent2020::repl::{help, run_command};
+use advent2020::selector::{select_puzzles, Selection};
+use advent2020::trace;
+use format::{records, to_json, Format};
+use options::{parse_options, Command, Options};
+use report::{to_html, to_markdown, DayReport};
+use server::{serve, Limits};
+use table::{Alignment, Table};
+use timing::{format_duration, Statistics};
+use watch::Watcher;
 
-fn main() -> Result<()> {
-    if let Some(day) = get_day_from_arguments() {
-        execute_puzzle(day);
+mod format;
+mod options;
+mod parallel;
+mod report;
+mod server;
+mod table;
+mod timing;
+mod watch;
+
+fn main() {
+    let options = parse_options(env::args().skip(1)).unwrap_or_else(|error| fail(error));
+    if options.verbosity > 0 && !trace::ENABLED {
+        eprintln!("Tracing is compiled out, build with --features trace to enable it");
+    }
+    trace::set_verbosity(options.verbosity);
+    match &options.command {
+        Command::List => list_puzzles(options.year),
+        Command::Run(selector) if options.watch => watch_puzzle(selector, &options),
+        Command::Run(selector) if options.example => execute_examples(selector, &options),
+        Command::Run(selector) => execute_puzzles(selector, &options),
+        Command::Bench { selector, runs } => bench_puzzles(selector, *runs, &options),
+        Command::Verify(selector) => verify_answers(selector, &options),
+        Command::Record(selector) => record_answers(selector, &options),
+        Command::Repl(selector) => start_repl(selector, &options),
+        Command::Serve { port } => start_server(*port),
+        Command::Report(selector) => write_report(selector, &options),
+        Command::Generate {
+            selector,
+            size,
+            seed,
+        } => generate_input(selector, *size, *seed, &options),
+        Command::Stress {
+            selector,
+            size,
+            seed,
+            runs,
+        } => stress_puzzles(selector, *size, *seed, *runs, &options),
+    }
+}
+
+fn fail(error: impl Display) -> ! {
+    eprintln!("{}", error);
+    exit(1);
+}
+
+fn list_puzzles(year: u16) {
+    for puzzle in puzzles_of(year) {
+        println!("{:>2} — {}", puzzle.day(), puzzle.title());
+    }
+}
+
+fn select(selector: &str, options: &Options) -> Vec<(Selection, String)> {
+    let selections = select_puzzles(options.year, selector).unwrap_or_else(|error| fail(error));
+    if options.input.is_some() && selections.len() > 1 {
+        fail("An input can only be given for a single day");
+    }
+    selections
+        .into_iter()
+        .map(|selection| {
+            let input = read_input(selection.puzzle, options.input.as_ref());
+            (selection, input)
+        })
+        .collect()
+}
+
+fn read_input(puzzle: &dyn Puzzle, input_override: Option<&InputSource>) -> String {
+    let source = InputSource::resolve(puzzle.year(), puzzle.day(), input_override);
+    source.read().unwrap_or_else(|error| {
+        fail(format!(
+            "Could not read input for day {} from {}: {}",
+            puzzle.day(),
+            source,
+            error
+        ))
+    })
+}
+
+fn execute(selection: &Selection, input: &str) -> Execution {
+    selection
+        .puzzle
+        .execute(input, &selection.parts)
+        .unwrap_or_else(|error| fail(error))
+}
+
+/// Executes the selections on `jobs` threads, emitting the executions in day order
+fn execute_in_order(
+    selections: Vec<(Selection, String)>,
+    jobs: usize,
+    mut emit: impl FnMut(&Selection, Execution),
+) {
+    parallel::run_ordered(
+        selections,
+        jobs,
+        |(selection, input)| {
+            let execution = selection.puzzle.execute(&input, &selection.parts);
+            (selection, execution)
+        },
+        |(selection, execution)| emit(&selection, execution.unwrap_or_else(|error| fail(error))),
+    );
+}
+
+fn execute_puzzles(selector: &str, options: &Options) {
+    let start = Instant::now();
+    let mut results = Vec::new();
+    execute_in_order(
+        select(selector, options),
+        options.jobs,
+        |selection, execution| {
+            if options.format == Format::Text {
+                print_execution(selection.puzzle, &execution, options.time);
+            }
+            results.push((selection.puzzle, execution));
+        },
+    );
+    let elapsed = start.elapsed();
+    if options.format == Format::Json {
+        let records = results
+            .iter()
+            .flat_map(|(puzzle, execution)| records(*puzzle, execution))
+            .collect::<Vec<_>>();
+        println!("{}", to_json(&records));
+    } else if results.len() > 1 {
+        println!();
+        print!("{}", summary(&results, options.time));
+        if options.time {
+            println!();
+            println!(
+                "Elapsed {} with {} job(s)",
+                format_duration(elapsed),
+                options.jobs
+            );
+        }
+    }
+}
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+fn watch_puzzle(selector: &str, options: &Options) {
+    if options.example || options.format != Format::Text {
+        fail("Watch mode only runs the input of a day with the text format");
+    }
+    let selection = match select_puzzles(options.year, selector).unwrap_or_else(|error| fail(error))
+    {
+        selections if selections.len() == 1 => selections.into_iter().next().unwrap(),
+        _ => fail("Only a single day can be watched"),
+    };
+    let source = InputSource::resolve(
+        selection.puzzle.year(),
+        selection.puzzle.day(),
+        options.input.as_ref(),
+    );
+    let mut watcher = match &source {
+        InputSource::File(path) => Watcher::new(path),
+        _ => fail(format!("Only an input file can be watched, not {}", source)),
+    };
+    loop {
+        print!("{}", CLEAR_SCREEN);
+        println!("Watching {} (Ctrl-C to stop)", source);
+        println!();
+        match source.read() {
+            Ok(input) => {
+                // A part panicking on an edited input should not stop the watch
+                let execution = catch_unwind(AssertUnwindSafe(|| {
+                    selection.puzzle.execute(&input, &selection.parts)
+                }));
+                match execution {
+                    Ok(Ok(execution)) => print_execution(selection.puzzle, &execution, true),
+                    Ok(Err(error)) => println!("{}", error),
+                    Err(_) => println!("Day {} panicked", selection.puzzle.day()),
+                }
+            }
+            Err(error) => println!("Could not read {}: {}", source, error),
+        }
+        stdout().flush().unwrap_or_else(|error| fail(error));
+        watcher.wait();
+    }
+}
+
+fn execute_examples(selector: &str, options: &Options) {
+    if options.input.is_some() {
+        fail("An input cannot be combined with --example");
+    }
+    if options.format != Format::Text {
+        fail("Examples can only be run with the text format");
+    }
+    let mut runs = Vec::new();
+    let mut examples = Vec::new();
+    let mut missing = Vec::new();
+    for selection in select_puzzles(options.year, selector).unwrap_or_else(|error| fail(error)) {
+        let puzzle = selection.puzzle;
+        let mut found = false;
+        for (number, example) in puzzle.examples().iter().enumerate() {
+            let parts = selection
+                .parts
+                .iter()
+                .copied()
+                .filter(|part| example.expected(*part).is_some())
+                .collect::<Vec<_>>();
+            if !parts.is_empty() {
+                found = true;
+                let puzzle = example.solver(puzzle);
+                runs.push((Selection { puzzle, parts }, example.input.to_string()));
+                examples.push((number + 1, example));
+            }
+        }
+        if !found {
+            missing.push(puzzle.day().to_string());
+        }
+    }
+    let mut examples = examples.into_iter();
+    execute_in_order(runs, options.jobs, |selection, execution| {
+        let (number, example) = examples.next().expect("One example per run");
+        let puzzle = selection.puzzle;
+        for result in &execution.results {
+            println!(
+                "{}:{} — {}: {} (example {}, expected {})",
+                puzzle.day(),
+                result.part,
+                puzzle.label(result.part),
+                result.answer,
+                number,
+                example
+                    .expected(result.part)
+                    .expect("Only parts with an expected answer are run")
+            );
+        }
+    });
+    if !missing.is_empty() {
+        println!("No example for day(s) {}", missing.join(", "));
+    }
+}
+
+fn print_execution(puzzle: &dyn Puzzle, execution: &Execution, time: bool) {
+    for result in &execution.results {
+        println!(
+            "{}:{} — {}: {}",
+            puzzle.day(),
+            result.part,
+            puzzle.label(result.part),
+            result.answer
+        );
+    }
+    if time {
+        let timings = execution
+            .results
+            .iter()
+            .map(|result| format!("part {} {}", result.part, format_duration(result.duration)))
+            .collect::<Vec<_>>();
+        println!(
+            "{} — parse {}, {}, total {}",
+            puzzle.day(),
+            format_duration(execution.parse_duration),
+            timings.join(", "),
+            format_duration(execution.duration())
+        );
+    }
+}
+
+fn summary(results: &[(&dyn Puzzle, Execution)], time: bool) -> Table {
+    let mut header = vec![
+        ("Day", Alignment::Right),
+        ("Title", Alignment::Left),
+        ("Part 1", Alignment::Right),
+        ("Part 2", Alignment::Right),
+    ];
+    if time {
+        header.extend(&[
+            ("Parse", Alignment::Right),
+            ("Time 1", Alignment::Right),
+            ("Time 2", Alignment::Right),
+            ("Total", Alignment::Right),
+        ]);
+    }
+    let mut table = Table::new(&header);
+    for (puzzle, execution) in results {
+        let mut row = vec![puzzle.day().to_string(), puzzle.title().to_string()];
+        row.extend(Part::ALL.iter().map(|part| {
+            execution
+                .answer(*part)
+                .map(ToString::to_string)
+                .unwrap_or_default()
+        }));
+        if time {
+            row.push(format_duration(execution.parse_duration));
+            row.extend(Part::ALL.iter().map(|part| {
+                execution
+                    .result(*part)
+                    .map(|result| format_duration(result.duration))
+                    .unwrap_or_default()
+            }));
+            row.push(format_duration(execution.duration()));
+        }
+        table.push(row);
+    }
+    table
+}
+
+fn bench_puzzles(selector: &str, runs: usize, options: &Options) {
+    let mut table = Table::new(&[
+        ("Day", Alignment::Right),
+        ("Step", Alignment::Left),
+        ("Min", Alignment::Right),
+        ("Median", Alignment::Right),
+        ("Max", Alignment::Right),
+    ]);
+    for (selection, input) in select(selector, options) {
+        let executions = (0..runs)
+            .map(|_| execute(&selection, &input))
+            .collect::<Vec<_>>();
+        let mut steps = vec![(
+            "parse".to_string(),
+            executions
+                .iter()
+                .map(|execution| execution.parse_duration)
+                .collect::<Vec<_>>(),
+        )];
+        steps.extend(selection.parts.iter().map(|part| {
+            let durations = executions
+                .iter()
+                .flat_map(|execution| execution.result(*part))
+                .map(|result| result.duration)
+                .collect();
+            (format!("part {}", part), durations)
+        }));
+        for (step, durations) in steps {
+            if let Some(statistics) = Statistics::of(&durations) {
+                table.push(vec![
+                    selection.puzzle.day().to_string(),
+                    step,
+                    format_duration(statistics.min),
+                    format_duration(statistics.median),
+                    format_duration(statistics.max),
+                ]);
+            }
+        }
+    }
+    println!("{} runs per day", runs);
+    println!();
+    print!("{}", table);
+}
+
+fn answers_path(options: &Options) -> PathBuf {
+    options
+        .answers
+        .clone()
+        .unwrap_or_else(|| default_answers_path(options.year))
+}
+
+fn verify_answers(selector: &str, options: &Options) {
+    let answers = match &options.answers {
+        Some(path) => Answers::load(path),
+        None => Answers::load_default(options.year),
+    }
+    .unwrap_or_else(|error| fail(error));
+    let mut mismatches = 0;
+    execute_in_order(
+        select(selector, options),
+        options.jobs,
+        |selection, execution| {
+            let puzzle = selection.puzzle;
+            for result in &execution.results {
+                let day_part = format!("{}:{}", puzzle.day(), result.part);
+                match answers.check(puzzle.day(), result.part, &result.answer) {
+                    Check::Match => println!("{} ok", day_part),
+                    Check::Mismatch(expected) => {
+                        mismatches += 1;
+                        println!(
+                            "{} MISMATCH — expected {}, actual {}",
+                            day_part, expected, result.answer
+                        );
+                    }
+                    Check::Unrecorded => {
+                        println!("{} unrecorded — actual {}", day_part, result.answer)
+                    }
+                }
+            }
+        },
+    );
+    if mismatches > 0 {
+        fail(format!("{} answer(s) do not match", mismatches));
+    }
+}
+
+fn record_answers(selector: &str, options: &Options) {
+    let path = answers_path(options);
+    let mut answers = Answers::load_or_default(&path).unwrap_or_else(|error| fail(error));
+    let mut recorded = 0;
+    execute_in_order(
+        select(selector, options),
+        options.jobs,
+        |selection, execution| {
+            for result in execution.results {
+                answers.record(selection.puzzle.day(), result.part, result.answer);
+                recorded += 1;
+            }
+        },
+    );
+    answers.save(&path).unwrap_or_else(|error| fail(error));
+    println!("Recorded {} answer(s) in {}", recorded, path.display());
+}
+
+fn start_repl(selector: &str, options: &Options) {
+    let puzzle = match select_puzzles(options.year, selector).unwrap_or_else(|error| fail(error)) {
+        selections if selections.len() == 1 => selections[0].puzzle,
+        _ => fail("The REPL explores a single day"),
+    };
+    let (puzzle, input) = if options.example {
+        match puzzle.examples().first() {
+            Some(example) => (example.solver(puzzle), example.input.to_string()),
+            None => fail(format!("Day {} has no example", puzzle.day())),
+        }
+    } else {
+        (puzzle, read_input(puzzle, options.input.as_ref()))
+    };
+    let mut session = match puzzle.session(&input) {
+        Ok(Some(session)) => session,
+        Ok(None) => fail(format!("Day {} has no REPL commands", puzzle.day())),
+        Err(error) => fail(error),
+    };
+    println!(
+        "{} — {}. Type help for the commands, quit to leave.",
+        puzzle.day(),
+        puzzle.title()
+    );
+    let mut lines = stdin().lock().lines();
+    loop {
+        print!("{}> ", session.namespace());
+        stdout().flush().unwrap_or_else(|error| fail(error));
+        let line = match lines.next() {
+            Some(line) => line.unwrap_or_else(|error| fail(error)),
+            None => break,
+        };
+        match line.trim() {
+            "help" => println!("{}", help(session.as_ref())),
+            "quit" | "exit" => break,
+            line => match run_command(session.as_mut(), line) {
+                Ok(output) if output.is_empty() => {}
+                Ok(output) => println!("{}", output.trim_end()),
+                Err(error) => println!("{}", error),
+            },
+        }
+    }
+}
+
+fn start_server(port: u16) {
+    let listener =
+        TcpListener::bind((Ipv4Addr::LOCALHOST, port)).unwrap_or_else(|error| fail(error));
+    let limits = Limits::default();
+    let paths = years()
+        .iter()
+        .map(|year| format!("/{}/day/N/part/P", year))
+        .collect::<Vec<_>>();
+    println!(
+        "Listening on http://{}, POST inputs of at most {} bytes to {}",
+        listener.local_addr().unwrap_or_else(|error| fail(error)),
+        limits.max_body_size,
+        paths.join(" or ")
+    );
+    serve(listener, limits).unwrap_or_else(|error| fail(error));
+}
+
+fn write_report(selector: &str, options: &Options) {
+    let mut days = Vec::new();
+    parallel::run_ordered(
+        select(selector, options),
+        options.jobs,
+        |(selection, input)| {
+            (
+                selection.puzzle,
+                selection
+                    .puzzle
+                    .execute_with_visuals(&input, &selection.parts),
+            )
+        },
+        |(puzzle, result)| {
+            let (execution, visuals) = result.unwrap_or_else(|error| fail(error));
+            days.push(DayReport {
+                puzzle,
+                execution,
+                visuals,
+            });
+        },
+    );
+    let report = if options.html {
+        to_html(&days)
     } else {
-        println!("Missing day argument");
-    }
-    Ok(())
-}
-
-fn get_day_from_arguments() -> Option<String> {
-    env::args().nth(1)
-}
-
-fn execute_puzzle(day: String) {
-    match day.as_str() {
-        "1" => puzzle::day1::execute(),
-        "2" => puzzle::day2::execute(),
-        "3" => puzzle::day3::execute(),
-        "4" => puzzle::day4::execute(),
-        "5" => puzzle::day5::execute(),
-        "6" => puzzle::day6::execute(),
-        "7" => puzzle::day7::execute(),
-        "8" => puzzle::day8::execute(),
-        "9" => puzzle::day9::execute(),
-        "10" => puzzle::day10::execute(),
-        "11" => puzzle::day11::execute(),
-        "12" => puzzle::day12::execute(),
-        "13" => puzzle::day13::execute(),
-        "14" => puzzle::day14::execute(),
-        "15" => puzzle::day15::execute(),
-        "16" => puzzle::day16::execute(),
-        "17" => puzzle::day17::execute(),
-        _ => println!("Unknown day: {}", day),
+        to_markdown(&days)
+    };
+    match &options.output {
+        Some(path) => {
+            write(path, report).unwrap_or_else(|error| {
+                fail(format!("Could not write {}: {}", path.display(), error))
+            });
+            println!("Report written to {}", path.display());
+        }
+        None => print!("{}", report),
+    }
+}
+
+fn generate_input(selector: &str, size: usize, seed: u64, options: &Options) {
+    let puzzle = match select_puzzles(options.year, selector).unwrap_or_else(|error| fail(error)) {
+        selections if selections.len() == 1 => selections[0].puzzle,
+        _ => fail("An input is generated for a single day"),
+    };
+    let generated = puzzle
+        .generate(seed, size)
+        .unwrap_or_else(|| fail(format!("Day {} has no generator", puzzle.day())));
+    match &options.output {
+        Some(path) => write(path, generated.input)
+            .unwrap_or_else(|error| fail(format!("Could not write {}: {}", path.display(), error))),
+        None => println!("{}", generated.input),
+    }
+}
+
+/// Solves generated inputs for consecutive seeds, checking the answers their generator knows
+fn stress_puzzles(selector: &str, size: usize, seed: u64, runs: usize, options: &Options) {
+    let mut failures = 0;
+    let mut missing = Vec::new();
+    for selection in select_puzzles(options.year, selector).unwrap_or_else(|error| fail(error)) {
+        let puzzle = selection.puzzle;
+        for seed in seed..seed + runs as u64 {
+            let generated = match puzzle.generate(seed, size) {
+                Some(generated) => generated,
+                None => {
+                    missing.push(puzzle.day().to_string());
+                    break;
+                }
+            };
+            let execution = catch_unwind(AssertUnwindSafe(|| {
+                puzzle.execute(&generated.input, &selection.parts)
+            }));
+            let execution = match execution {
+                Ok(Ok(execution)) => execution,
+                Ok(Err(error)) => {
+                    failures += 1;
+                    println!("{} INVALID — seed {}: {}", puzzle.day(), seed, error);
+                    continue;
+                }
+                Err(_) => {
+                    failures += 1;
+                    println!("{} PANIC — seed {}", puzzle.day(), seed);
+                    continue;
+                }
+            };
+            for result in &execution.results {
+                let day_part = format!("{}:{}", puzzle.day(), result.part);
+                let time = format_duration(result.duration);
+                match generated.expected(result.part) {
+                    Some(expected) if expected == result.answer => {
+                        println!("{} ok — seed {}, {}", day_part, seed, time)
+                    }
+                    Some(expected) => {
+                        failures += 1;
+                        println!(
+                            "{} MISMATCH — seed {}, expected {}, actual {}",
+                            day_part, seed, expected, result.answer
+                        );
+                    }
+                    None => println!(
+                        "{} unchecked — seed {}, actual {}, {}",
+                        day_part, seed, result.answer, time
+                    ),
+                }
+            }
+        }
+    }
+    if !missing.is_empty() {
+        println!("No generator for day(s) {}", missing.join(", "));
+    }
+    if failures > 0 {
+        fail(format!("{} run(s) failed", failures));
     }
 }
@@ -0,0 +1,256 @@
+//! Number theory over `i128`, with intermediate products computed in `u128` so that they do not
+//! overflow.
+
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+use crate::trace::trace;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum MathError {
+    InvalidModulus(i128),
+    NotInvertible { value: i128, modulus: i128 },
+    NoSolution,
+    Overflow,
+}
+
+impl Display for MathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MathError::InvalidModulus(modulus) => {
+                write!(f, "Modulus must be positive: {}", modulus)
+            }
+            MathError::NotInvertible { value, modulus } => {
+                write!(f, "{} has no inverse modulo {}", value, modulus)
+            }
+            MathError::NoSolution => write!(f, "Congruences are incompatible"),
+            MathError::Overflow => write!(f, "Result does not fit in 128 bits"),
+        }
+    }
+}
+
+impl std::error::Error for MathError {}
+
+/// Returns `(g, x, y)` with `g` the non-negative gcd of `a` and `b`, and `a * x + b * y = g`
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+pub fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+pub fn lcm(a: u128, b: u128) -> Result<u128, MathError> {
+    if a == 0 || b == 0 {
+        return Ok(0);
+    }
+    (a / gcd(a, b)).checked_mul(b).ok_or(MathError::Overflow)
+}
+
+/// The inverse in `[0, modulus)`
+pub fn mod_inverse(value: i128, modulus: i128) -> Result<i128, MathError> {
+    if modulus <= 0 {
+        return Err(MathError::InvalidModulus(modulus));
+    }
+    match extended_gcd(value.rem_euclid(modulus), modulus) {
+        (1, x, _) => Ok(x.rem_euclid(modulus)),
+        _ => Err(MathError::NotInvertible { value, modulus }),
+    }
+}
+
+pub fn mod_pow(base: u128, mut exponent: u128, modulus: u128) -> Result<u128, MathError> {
+    if modulus == 0 {
+        return Err(MathError::InvalidModulus(0));
+    }
+    let mut base = base % modulus;
+    let mut result = 1 % modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    Ok(result)
+}
+
+/// Both operands must be lower than the modulus
+fn add_mod(a: u128, b: u128, modulus: u128) -> u128 {
+    if a >= modulus - b {
+        a - (modulus - b)
+    } else {
+        a + b
+    }
+}
+
+fn mul_mod(a: u128, mut b: u128, modulus: u128) -> u128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product % modulus;
+    }
+    let (mut a, mut result) = (a % modulus, 0);
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, modulus);
+        }
+        a = add_mod(a, a, modulus);
+        b >>= 1;
+    }
+    result
+}
+
+/// Solves `x ≡ residue (mod modulus)` for each `(residue, modulus)`, returning the smallest
+/// non-negative `x` and the lcm of the moduli, which do not need to be coprime
+pub fn chinese_remainder(congruences: &[(i128, i128)]) -> Result<(i128, i128), MathError> {
+    congruences
+        .iter()
+        .try_fold((0, 1), |(x, lcm), &(residue, modulus)| {
+            if modulus <= 0 {
+                return Err(MathError::InvalidModulus(modulus));
+            }
+            let residue = residue.rem_euclid(modulus);
+            let g = gcd(lcm as u128, modulus as u128) as i128;
+            let difference = residue - x;
+            if difference % g != 0 {
+                return Err(MathError::NoSolution);
+            }
+            let reduced_modulus = modulus / g;
+            let merged_lcm = (lcm / g).checked_mul(modulus).ok_or(MathError::Overflow)?;
+            let steps = mul_mod(
+                (difference / g).rem_euclid(reduced_modulus) as u128,
+                mod_inverse(lcm / g, reduced_modulus)? as u128,
+                reduced_modulus as u128,
+            );
+            // x + lcm * steps stays below the merged lcm, which fits in an i128
+            let merged = x + lcm * i128::try_from(steps).map_err(|_| MathError::Overflow)?;
+            trace!(Debug, "x ≡ {} (mod {})", merged, merged_lcm);
+            Ok((merged, merged_lcm))
+        })
+}
+
+#[cfg(test)]
+mod extended_gcd_should {
+    use super::*;
+
+    #[test]
+    fn return_the_gcd_with_bezout_coefficients() {
+        let (g, x, y) = extended_gcd(240, 46);
+
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+    }
+
+    #[test]
+    fn return_a_non_negative_gcd() {
+        let (g, x, y) = extended_gcd(-12, 18);
+
+        assert_eq!(g, 6);
+        assert_eq!(-12 * x + 18 * y, 6);
+    }
+}
+
+#[cfg(test)]
+mod lcm_should {
+    use super::*;
+
+    #[test]
+    fn return_the_least_common_multiple() {
+        assert_eq!(lcm(4, 6), Ok(12));
+    }
+
+    #[test]
+    fn fail_on_overflow() {
+        assert_eq!(lcm(u128::MAX, u128::MAX - 1), Err(MathError::Overflow));
+    }
+}
+
+#[cfg(test)]
+mod mod_inverse_should {
+    use super::*;
+
+    #[test]
+    fn return_the_inverse_within_the_modulus() {
+        assert_eq!(mod_inverse(3, 11), Ok(4));
+        assert_eq!(mod_inverse(-3, 11), Ok(7));
+    }
+
+    #[test]
+    fn fail_when_value_and_modulus_are_not_coprime() {
+        assert_eq!(
+            mod_inverse(4, 6),
+            Err(MathError::NotInvertible {
+                value: 4,
+                modulus: 6
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod mod_pow_should {
+    use super::*;
+
+    #[test]
+    fn raise_to_a_power_modulo() {
+        assert_eq!(mod_pow(7, 8, 20201227), Ok(5764801));
+        assert_eq!(mod_pow(5, 0, 1), Ok(0));
+    }
+
+    #[test]
+    fn not_overflow_with_a_modulus_above_64_bits() {
+        let modulus = u128::MAX - 158;
+
+        assert_eq!(mod_pow(2, 128, modulus), Ok(159));
+    }
+}
+
+#[cfg(test)]
+mod chinese_remainder_should {
+    use super::*;
+
+    #[test]
+    fn solve_coprime_moduli() {
+        assert_eq!(
+            chinese_remainder(&[(0, 17), (-2, 13), (-3, 19)]),
+            Ok((3417, 4199))
+        );
+    }
+
+    #[test]
+    fn solve_moduli_sharing_a_factor() {
+        assert_eq!(chinese_remainder(&[(2, 6), (5, 9)]), Ok((14, 18)));
+    }
+
+    #[test]
+    fn fail_on_incompatible_congruences() {
+        assert_eq!(
+            chinese_remainder(&[(1, 6), (2, 4)]),
+            Err(MathError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn fail_when_the_moduli_overflow() {
+        let modulus = i128::MAX / 3;
+
+        assert_eq!(
+            chinese_remainder(&[(0, modulus), (0, modulus - 2)]),
+            Err(MathError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,515 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+use crate::format::Format;
+use advent2020::puzzle::input::InputSource;
+use advent2020::puzzle::DEFAULT_YEAR;
+
+#[derive(PartialEq, Debug)]
+pub enum OptionsError {
+    MissingSelector,
+    MissingValue(String),
+    InvalidValue(String, String),
+    UnknownOption(String),
+    UnexpectedArgument(String),
+}
+
+impl Display for OptionsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionsError::MissingSelector => {
+                write!(
+                    f,
+                    "Missing day argument (e.g. 14, 14:1, 1-9, 3,7,14 or all)"
+                )
+            }
+            OptionsError::MissingValue(option) => write!(f, "Missing value for {}", option),
+            OptionsError::InvalidValue(option, value) => {
+                write!(f, "Invalid value for {}: {}", option, value)
+            }
+            OptionsError::UnknownOption(option) => write!(f, "Unknown option: {}", option),
+            OptionsError::UnexpectedArgument(argument) => {
+                write!(f, "Unexpected argument: {}", argument)
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Command {
+    List,
+    Run(String),
+    Bench {
+        selector: String,
+        runs: usize,
+    },
+    Verify(String),
+    Record(String),
+    Repl(String),
+    Serve {
+        port: u16,
+    },
+    Report(String),
+    Generate {
+        selector: String,
+        size: usize,
+        seed: u64,
+    },
+    Stress {
+        selector: String,
+        size: usize,
+        seed: u64,
+        runs: usize,
+    },
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Options {
+    pub command: Command,
+    pub year: u16,
+    pub input: Option<InputSource>,
+    pub time: bool,
+    pub format: Format,
+    pub answers: Option<PathBuf>,
+    pub jobs: usize,
+    pub example: bool,
+    pub watch: bool,
+    pub html: bool,
+    pub output: Option<PathBuf>,
+    pub verbosity: u8,
+}
+
+const DEFAULT_BENCH_RUNS: usize = 10;
+const DEFAULT_SELECTOR: &str = "all";
+const DEFAULT_JOBS: usize = 1;
+const DEFAULT_PORT: u16 = 2020;
+const DEFAULT_GENERATED_SIZE: usize = 1000;
+const DEFAULT_SEED: u64 = 0;
+const DEFAULT_STRESS_RUNS: usize = 3;
+
+pub fn parse_options<I>(arguments: I) -> Result<Options, OptionsError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut arguments = arguments.into_iter();
+    let mut positional = Vec::new();
+    let mut input = None;
+    let mut time = false;
+    let mut runs = None;
+    let mut format = Format::Text;
+    let mut answers = None;
+    let mut jobs = DEFAULT_JOBS;
+    let mut example = false;
+    let mut watch = false;
+    let mut port = None;
+    let mut html = false;
+    let mut output = None;
+    let mut verbosity = 0;
+    let mut size = DEFAULT_GENERATED_SIZE;
+    let mut seed = DEFAULT_SEED;
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--input" | "-i" => {
+                let value = next_value(&mut arguments, &argument)?;
+                input = Some(InputSource::from(value.as_str()));
+            }
+            "--time" | "-t" => time = true,
+            "--example" | "-e" => example = true,
+            "--watch" | "-w" => watch = true,
+            "--html" => html = true,
+            "--verbose" | "-v" => verbosity += 1,
+            "-vv" => verbosity += 2,
+            "--output" | "-o" => output = Some(next_value(&mut arguments, &argument)?.into()),
+            "--format" | "-f" => {
+                let value = next_value(&mut arguments, &argument)?;
+                format = value
+                    .parse()
+                    .map_err(|_| OptionsError::InvalidValue(argument, value))?;
+            }
+            "--runs" => {
+                let value = next_value(&mut arguments, &argument)?;
+                runs = match value.parse() {
+                    Ok(runs) if runs > 0 => Some(runs),
+                    _ => return Err(OptionsError::InvalidValue(argument, value)),
+                };
+            }
+            "--jobs" | "-j" => {
+                let value = next_value(&mut arguments, &argument)?;
+                jobs = match value.parse() {
+                    Ok(jobs) if jobs > 0 => jobs,
+                    _ => return Err(OptionsError::InvalidValue(argument, value)),
+                };
+            }
+            "--port" => {
+                let value = next_value(&mut arguments, &argument)?;
+                port = Some(
+                    value
+                        .parse()
+                        .map_err(|_| OptionsError::InvalidValue(argument, value))?,
+                );
+            }
+            "--size" => {
+                let value = next_value(&mut arguments, &argument)?;
+                size = match value.parse() {
+                    Ok(size) if size > 0 => size,
+                    _ => return Err(OptionsError::InvalidValue(argument, value)),
+                };
+            }
+            "--seed" => {
+                let value = next_value(&mut arguments, &argument)?;
+                seed = value
+                    .parse()
+                    .map_err(|_| OptionsError::InvalidValue(argument, value))?;
+            }
+            "--answers" => answers = Some(next_value(&mut arguments, &argument)?.into()),
+            // A bare dash is left to the selector, as it stands for the standard input
+            option if option.starts_with('-') && option != "-" => {
+                return Err(OptionsError::UnknownOption(argument));
+            }
+            _ => positional.push(argument),
+        }
+    }
+    let year = match positional.iter().position(|argument| is_year(argument)) {
+        Some(index) => positional
+            .remove(index)
+            .parse()
+            .expect("Four digits always fit in a u16"),
+        None => DEFAULT_YEAR,
+    };
+    let mut positional = positional.into_iter();
+    let command = match positional.next().as_deref() {
+        Some("list") => Command::List,
+        Some("bench") => Command::Bench {
+            selector: positional.next().ok_or(OptionsError::MissingSelector)?,
+            runs: runs.unwrap_or(DEFAULT_BENCH_RUNS),
+        },
+        Some("verify") => {
+            Command::Verify(positional.next().unwrap_or_else(|| DEFAULT_SELECTOR.into()))
+        }
+        Some("repl") => Command::Repl(positional.next().ok_or(OptionsError::MissingSelector)?),
+        Some("serve") => Command::Serve {
+            port: port.unwrap_or(DEFAULT_PORT),
+        },
+        Some("report") => {
+            Command::Report(positional.next().unwrap_or_else(|| DEFAULT_SELECTOR.into()))
+        }
+        Some("record") => {
+            Command::Record(positional.next().unwrap_or_else(|| DEFAULT_SELECTOR.into()))
+        }
+        Some("generate") => Command::Generate {
+            selector: positional.next().ok_or(OptionsError::MissingSelector)?,
+            size,
+            seed,
+        },
+        Some("stress") => Command::Stress {
+            selector: positional.next().unwrap_or_else(|| DEFAULT_SELECTOR.into()),
+            size,
+            seed,
+            runs: runs.unwrap_or(DEFAULT_STRESS_RUNS),
+        },
+        Some(selector) => Command::Run(selector.into()),
+        None => return Err(OptionsError::MissingSelector),
+    };
+    match positional.next() {
+        Some(argument) => Err(OptionsError::UnexpectedArgument(argument)),
+        None => Ok(Options {
+            command,
+            year,
+            input,
+            time,
+            format,
+            answers,
+            jobs,
+            example,
+            watch,
+            html,
+            output,
+            verbosity,
+        }),
+    }
+}
+
+/// Days never have four digits, so such an argument can only be a year
+fn is_year(argument: &str) -> bool {
+    argument.len() == 4 && argument.chars().all(|c| c.is_ascii_digit())
+}
+
+fn next_value(
+    arguments: &mut impl Iterator<Item = String>,
+    option: &str,
+) -> Result<String, OptionsError> {
+    arguments
+        .next()
+        .ok_or_else(|| OptionsError::MissingValue(option.into()))
+}
+
+#[cfg(test)]
+mod parse_options_should {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<Options, OptionsError> {
+        parse_options(arguments.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn return_a_run_command_with_the_selector() {
+        assert_eq!(
+            parse(&["1-9"]),
+            Ok(Options {
+                command: Command::Run("1-9".into()),
+                year: 2020,
+                input: None,
+                time: false,
+                format: Format::Text,
+                answers: None,
+                jobs: 1,
+                example: false,
+                watch: false,
+                html: false,
+                output: None,
+                verbosity: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn return_the_year_before_the_selector() {
+        let options = parse(&["2020", "14:2"]).unwrap();
+
+        assert_eq!(options.year, 2020);
+        assert_eq!(options.command, Command::Run("14:2".into()));
+    }
+
+    #[test]
+    fn return_the_year_after_the_command() {
+        let options = parse(&["verify", "2016"]).unwrap();
+
+        assert_eq!(options.year, 2016);
+        assert_eq!(options.command, Command::Verify("all".into()));
+    }
+
+    #[test]
+    fn return_the_list_command() {
+        assert_eq!(parse(&["list"]).map(|o| o.command), Ok(Command::List));
+    }
+
+    #[test]
+    fn return_the_input_file() {
+        assert_eq!(
+            parse(&["--input", "my_input", "14"]).map(|o| o.input),
+            Ok(Some(InputSource::File("my_input".into())))
+        );
+    }
+
+    #[test]
+    fn return_stdin_as_input_for_a_dash() {
+        assert_eq!(
+            parse(&["14", "-i", "-"]).map(|o| o.input),
+            Ok(Some(InputSource::Stdin))
+        );
+    }
+
+    #[test]
+    fn enable_timing() {
+        assert_eq!(parse(&["--time", "1-9"]).map(|o| o.time), Ok(true));
+    }
+
+    #[test]
+    fn enable_the_example_mode() {
+        assert_eq!(parse(&["7", "--example"]).map(|o| o.example), Ok(true));
+    }
+
+    #[test]
+    fn enable_the_watch_mode() {
+        assert_eq!(parse(&["8", "-w"]).map(|o| o.watch), Ok(true));
+    }
+
+    #[test]
+    fn count_the_verbosity() {
+        assert_eq!(parse(&["16", "-v"]).map(|o| o.verbosity), Ok(1));
+        assert_eq!(parse(&["16", "-vv"]).map(|o| o.verbosity), Ok(2));
+        assert_eq!(
+            parse(&["16", "--verbose", "--verbose"]).map(|o| o.verbosity),
+            Ok(2)
+        );
+    }
+
+    #[test]
+    fn return_the_output_format() {
+        assert_eq!(
+            parse(&["all", "--format", "json"]).map(|o| o.format),
+            Ok(Format::Json)
+        );
+    }
+
+    #[test]
+    fn fail_on_an_unknown_format() {
+        assert_eq!(
+            parse(&["all", "-f", "xml"]),
+            Err(OptionsError::InvalidValue("-f".into(), "xml".into()))
+        );
+    }
+
+    #[test]
+    fn return_a_bench_command_with_ten_runs_by_default() {
+        assert_eq!(
+            parse(&["bench", "15"]).map(|o| o.command),
+            Ok(Command::Bench {
+                selector: "15".into(),
+                runs: 10
+            })
+        );
+    }
+
+    #[test]
+    fn return_a_bench_command_with_the_given_number_of_runs() {
+        assert_eq!(
+            parse(&["bench", "all", "--runs", "3"]).map(|o| o.command),
+            Ok(Command::Bench {
+                selector: "all".into(),
+                runs: 3
+            })
+        );
+    }
+
+    #[test]
+    fn fail_when_the_number_of_runs_is_not_positive() {
+        assert_eq!(
+            parse(&["bench", "all", "--runs", "0"]),
+            Err(OptionsError::InvalidValue("--runs".into(), "0".into()))
+        );
+    }
+
+    #[test]
+    fn return_the_number_of_jobs() {
+        assert_eq!(parse(&["all", "--jobs", "4"]).map(|o| o.jobs), Ok(4));
+    }
+
+    #[test]
+    fn fail_when_the_number_of_jobs_is_not_positive() {
+        assert_eq!(
+            parse(&["all", "-j", "0"]),
+            Err(OptionsError::InvalidValue("-j".into(), "0".into()))
+        );
+    }
+
+    #[test]
+    fn return_a_verify_command_for_all_days_by_default() {
+        assert_eq!(
+            parse(&["verify"]).map(|o| o.command),
+            Ok(Command::Verify("all".into()))
+        );
+    }
+
+    #[test]
+    fn return_a_record_command_with_the_answers_file() {
+        let options = parse(&["record", "14:2", "--answers", "answers.toml"]).unwrap();
+
+        assert_eq!(options.command, Command::Record("14:2".into()));
+        assert_eq!(options.answers, Some("answers.toml".into()));
+    }
+
+    #[test]
+    fn return_a_repl_command_with_the_day() {
+        assert_eq!(
+            parse(&["repl", "7", "--example"]).map(|o| o.command),
+            Ok(Command::Repl("7".into()))
+        );
+    }
+
+    #[test]
+    fn fail_when_the_repl_has_no_day() {
+        assert_eq!(parse(&["repl"]), Err(OptionsError::MissingSelector));
+    }
+
+    #[test]
+    fn return_a_serve_command_on_port_2020_by_default() {
+        assert_eq!(
+            parse(&["serve"]).map(|o| o.command),
+            Ok(Command::Serve { port: 2020 })
+        );
+    }
+
+    #[test]
+    fn return_a_serve_command_with_the_given_port() {
+        assert_eq!(
+            parse(&["serve", "--port", "8080"]).map(|o| o.command),
+            Ok(Command::Serve { port: 8080 })
+        );
+    }
+
+    #[test]
+    fn return_a_report_command_with_its_output() {
+        let options = parse(&["report", "--html", "-o", "report.html"]).unwrap();
+
+        assert_eq!(options.command, Command::Report("all".into()));
+        assert!(options.html);
+        assert_eq!(options.output, Some("report.html".into()));
+    }
+
+    #[test]
+    fn return_a_generate_command_with_its_size_and_seed() {
+        assert_eq!(
+            parse(&["generate", "8", "--size", "5000", "--seed", "42"]).map(|o| o.command),
+            Ok(Command::Generate {
+                selector: "8".into(),
+                size: 5000,
+                seed: 42
+            })
+        );
+    }
+
+    #[test]
+    fn fail_when_the_generated_size_is_not_positive() {
+        assert_eq!(
+            parse(&["generate", "8", "--size", "0"]),
+            Err(OptionsError::InvalidValue("--size".into(), "0".into()))
+        );
+    }
+
+    #[test]
+    fn return_a_stress_command_for_all_days_by_default() {
+        assert_eq!(
+            parse(&["stress"]).map(|o| o.command),
+            Ok(Command::Stress {
+                selector: "all".into(),
+                size: 1000,
+                seed: 0,
+                runs: 3
+            })
+        );
+    }
+
+    #[test]
+    fn fail_when_input_has_no_value() {
+        assert_eq!(
+            parse(&["14", "--input"]),
+            Err(OptionsError::MissingValue("--input".into()))
+        );
+    }
+
+    #[test]
+    fn fail_when_there_is_no_selector() {
+        assert_eq!(parse(&[]), Err(OptionsError::MissingSelector));
+    }
+
+    #[test]
+    fn fail_on_an_unknown_option() {
+        assert_eq!(
+            parse(&["14", "--fast"]),
+            Err(OptionsError::UnknownOption("--fast".into()))
+        );
+    }
+
+    #[test]
+    fn fail_on_an_unknown_short_option() {
+        assert_eq!(
+            parse(&["-x", "14"]),
+            Err(OptionsError::UnknownOption("-x".into()))
+        );
+        assert_eq!(
+            parse(&["14", "-vvv"]),
+            Err(OptionsError::UnknownOption("-vvv".into()))
+        );
+    }
+}
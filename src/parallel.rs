@@ -0,0 +1,77 @@
+use std::sync::mpsc::channel;
+use std::sync::Mutex;
+use std::thread;
+
+/// Runs the items on `jobs` threads and emits the results in the order of the items, as soon as
+/// all the previous ones are emitted
+pub fn run_ordered<T, R>(
+    items: Vec<T>,
+    jobs: usize,
+    run: impl Fn(T) -> R + Sync,
+    mut emit: impl FnMut(R),
+) where
+    T: Send,
+    R: Send,
+{
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let (sender, receiver) = channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.max(1) {
+            let sender = sender.clone();
+            let (queue, run) = (&queue, &run);
+            scope.spawn(move || loop {
+                let item = queue.lock().unwrap().next();
+                match item {
+                    Some((index, item)) => {
+                        if sender.send((index, run(item))).is_err() {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            });
+        }
+        drop(sender);
+        let mut pending = Vec::new();
+        let mut next = 0;
+        for (index, result) in receiver {
+            pending.push((index, result));
+            while let Some(position) = pending.iter().position(|(index, _)| *index == next) {
+                emit(pending.swap_remove(position).1);
+                next += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod run_ordered_should {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn emit_the_results_in_the_order_of_the_items() {
+        let mut results = Vec::new();
+
+        run_ordered(
+            vec![30, 0, 20, 10, 0],
+            3,
+            |delay| {
+                thread::sleep(Duration::from_millis(delay));
+                delay * 2
+            },
+            |result| results.push(result),
+        );
+
+        assert_eq!(results, vec![60, 0, 40, 20, 0]);
+    }
+
+    #[test]
+    fn run_everything_with_a_single_job() {
+        let mut results = Vec::new();
+
+        run_ordered(vec![1, 2, 3], 1, |n| n + 1, |result| results.push(result));
+
+        assert_eq!(results, vec![2, 3, 4]);
+    }
+}
@@ -1,24 +1,69 @@
-use super::input::read_lines;
-
-pub fn execute() {
-    let entries = get_entries();
-    println!(
-        "1:1 — Product of two entries that sum to 2020: {}",
-        find_product_of_pair_with_sum(2020, 0, &entries).unwrap()
-    );
-    println!(
-        "1:2 — Product of three entries that sum to 2020: {}",
-        find_product_of_triplet_with_sum(2020, 0, &entries).unwrap()
-    );
-}
-
-fn get_entries() -> Vec<u32> {
-    read_lines("day1")
-        .unwrap()
-        .iter()
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse().unwrap())
-        .collect()
+use std::collections::HashSet;
+
+use super::{Answer, Example, Generated, Solution};
+use crate::error::ParseError;
+use crate::puzzle::input::read_parsed;
+use crate::random::Random;
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Model = Vec<u32>;
+
+    fn day(&self) -> u8 {
+        1
+    }
+
+    fn title(&self) -> &'static str {
+        "Report Repair"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Product of two entries that sum to 2020",
+            "Product of three entries that sum to 2020",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<u32>, ParseError> {
+        get_entries(input)
+    }
+
+    fn part1(&self, entries: &Vec<u32>) -> Result<Answer, ParseError> {
+        find_product_of_pair_with_sum(2020, 0, entries)
+            .map(Answer::from)
+            .ok_or_else(|| ParseError::new("", "no two entries sum to 2020"))
+    }
+
+    fn part2(&self, entries: &Vec<u32>) -> Result<Answer, ParseError> {
+        find_product_of_triplet_with_sum(2020, 0, entries)
+            .map(Answer::from)
+            .ok_or_else(|| ParseError::new("", "no three entries sum to 2020"))
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_entries(random, size))
+    }
+}
+
+static EXAMPLES: &[Example] = &[Example {
+    input: r"1721
+979
+366
+299
+675
+1456",
+    part1: Some(514579),
+    part2: Some(241861950),
+    solver: None,
+}];
+
+fn get_entries(input: &str) -> Result<Vec<u32>, ParseError> {
+    read_parsed(input)
 }
 
 fn find_product_of_pair_with_sum(sum: u32, start: usize, entries: &[u32]) -> Option<u32> {
@@ -44,3 +89,79 @@ fn find_product_of_triplet_with_sum(sum: u32, start: usize, entries: &[u32]) ->
         .flat_map(|(i, v)| find_product_of_pair_with_sum(sum - v, i + 1, entries).map(|v2| v * v2))
         .next()
 }
+
+/// A single pair and a single triplet sum to 2020, the other entries are all greater than 1010
+/// and cannot complete either of them
+fn generate_entries(random: &mut Random, size: usize) -> Generated {
+    let (pair, triplet) = loop {
+        let a = random.between(20, 1009) as u32;
+        let c = random.between(20, 1000) as u32;
+        let d = random.between(20, 2000 - c as u64) as u32;
+        let (pair, triplet) = ([a, 2020 - a], [c, d, 2020 - c - d]);
+        if count_sums_to_2020(&pair, &triplet) == (1, 1) {
+            break (pair, triplet);
+        }
+    };
+    let mut entries = pair.iter().chain(&triplet).copied().collect::<Vec<_>>();
+    let completions = entries
+        .iter()
+        .flat_map(|a| entries.iter().map(move |b| a + b).chain(Some(*a)))
+        .collect::<HashSet<_>>();
+    while entries.len() < size {
+        let entry = random.between(1011, 1999) as u32;
+        if !completions.contains(&(2020 - entry)) {
+            entries.push(entry);
+        }
+    }
+    random.shuffle(&mut entries);
+    Generated {
+        input: entries
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        part1: Some((pair[0] * pair[1]) as i128),
+        part2: Some(triplet.iter().map(|v| *v as i128).product()),
+    }
+}
+
+/// Counts the pairs and the triplets, an entry possibly repeated
+fn count_sums_to_2020(pair: &[u32], triplet: &[u32]) -> (usize, usize) {
+    let values = pair.iter().chain(triplet).copied().collect::<Vec<_>>();
+    let n = values.len();
+    let pairs = (0..n)
+        .flat_map(|i| (i..n).map(move |j| (i, j)))
+        .filter(|(i, j)| values[*i] + values[*j] == 2020)
+        .count();
+    let triplets = (0..n)
+        .flat_map(|i| (i..n).flat_map(move |j| (j..n).map(move |k| (i, j, k))))
+        .filter(|(i, j, k)| values[*i] + values[*j] + values[*k] == 2020)
+        .count();
+    (pairs, triplets)
+}
+
+#[cfg(test)]
+mod part1_should {
+    use super::*;
+
+    #[test]
+    fn fail_when_no_two_entries_sum_to_2020() {
+        assert_eq!(
+            Day1.part1(&vec![1721, 979, 366]),
+            Err(ParseError::new("", "no two entries sum to 2020"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod part2_should {
+    use super::*;
+
+    #[test]
+    fn fail_when_no_three_entries_sum_to_2020() {
+        assert_eq!(
+            Day1.part2(&vec![1721, 299, 366]),
+            Err(ParseError::new("", "no three entries sum to 2020"))
+        );
+    }
+}
@@ -1,31 +1,115 @@
-use crate::puzzle::input::read_lines;
+use crate::error::ParseError;
+use crate::puzzle::input::read_parsed;
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
 use itertools::Itertools;
 use std::collections::HashSet;
 
-pub fn execute() {
-    let adapters = get_adapters();
-    let (diff1, diff3) = count_jolt_differences(&compute_adapter_chain(&adapters));
-    println!(
-        "10:1 — Product of 1-jolt differences and 3-jolts differences: {}",
-        diff1 * diff3,
-    );
-    println!(
-        "10:2 — Total number of adapter arrangements: {}",
-        total_arrangements(&adapters),
-    );
-}
-
-fn get_adapters() -> HashSet<u64> {
-    let mut adapters = read_lines("day10")
-        .unwrap()
-        .iter()
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse().unwrap())
-        .collect::<HashSet<_>>();
-    let max = adapters.iter().max().copied().unwrap();
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Model = HashSet<u64>;
+
+    fn day(&self) -> u8 {
+        10
+    }
+
+    fn title(&self) -> &'static str {
+        "Adapter Array"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Product of 1-jolt differences and 3-jolts differences",
+            "Total number of adapter arrangements",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<HashSet<u64>, ParseError> {
+        get_adapters(input)
+    }
+
+    fn part1(&self, adapters: &HashSet<u64>) -> Result<Answer, ParseError> {
+        let (diff1, diff3) = count_jolt_differences(&compute_adapter_chain(adapters));
+        Ok((diff1 * diff3).into())
+    }
+
+    fn part2(&self, adapters: &HashSet<u64>) -> Result<Answer, ParseError> {
+        total_arrangements(adapters)
+            .map(Answer::from)
+            .ok_or_else(|| {
+                ParseError::new("", "the number of arrangements does not fit in 64 bits")
+            })
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_adapters(random, size))
+    }
+}
+
+static EXAMPLES: &[Example] = &[
+    Example {
+        input: r"16
+10
+15
+5
+1
+11
+7
+19
+6
+12
+4",
+        part1: Some(35),
+        part2: Some(8),
+        solver: None,
+    },
+    Example {
+        input: r"28
+33
+18
+42
+31
+14
+46
+20
+48
+47
+24
+23
+49
+45
+19
+38
+39
+11
+1
+32
+25
+35
+8
+17
+7
+9
+4
+2
+34
+10
+3",
+        part1: Some(220),
+        part2: Some(19208),
+        solver: None,
+    },
+];
+
+fn get_adapters(input: &str) -> Result<HashSet<u64>, ParseError> {
+    let mut adapters = read_parsed(input)?.into_iter().collect::<HashSet<_>>();
     adapters.insert(0);
-    adapters.insert(max + 3);
-    adapters
+    Ok(adapters)
 }
 
 fn count_jolt_differences(chain: &[u64]) -> (usize, usize) {
@@ -40,10 +124,11 @@ fn count_jolt_differences(chain: &[u64]) -> (usize, usize) {
 fn compute_adapter_chain(adapters: &HashSet<u64>) -> Vec<u64> {
     let mut chain = vec![0];
     let mut current_joltage = 0;
-    while let Some(joltage) = find_adapter(current_joltage, &adapters) {
+    while let Some(joltage) = find_adapter(current_joltage, adapters) {
         current_joltage = joltage;
         chain.push(joltage);
     }
+    chain.push(current_joltage + 3);
     chain
 }
 
@@ -55,21 +140,28 @@ fn find_adapter(joltage: u64, adapters: &HashSet<u64>) -> Option<u64> {
         .copied()
 }
 
-fn total_arrangements(adapters: &HashSet<u64>) -> u64 {
+/// None when the number does not fit in 64 bits
+fn total_arrangements(adapters: &HashSet<u64>) -> Option<u64> {
     adapters
         .iter()
         .sorted()
         .copied()
         .contiguous()
-        .map(|group| nb_combinations(group.len()))
-        .product()
+        .try_fold(1u64, |product, group| {
+            product.checked_mul(nb_combinations(group.len())?)
+        })
 }
 
-fn nb_combinations(group_size: usize) -> u64 {
+/// Each number of combinations is the sum of the three previous ones
+fn nb_combinations(group_size: usize) -> Option<u64> {
+    let (mut a, mut b, mut c) = (0u64, 1u64, 1u64);
+    for _ in 2..group_size {
+        (a, b, c) = (b, c, a.checked_add(b)?.checked_add(c)?);
+    }
     match group_size {
-        0 => 0,
-        1 | 2 => 1,
-        n => nb_combinations(n - 1) + nb_combinations(n - 2) + nb_combinations(n - 3),
+        0 => Some(a),
+        1 => Some(b),
+        _ => Some(c),
     }
 }
 
@@ -88,8 +180,8 @@ where
     type Item = Vec<u64>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut values = self.previous_value.into_iter().collect::<Vec<_>>();
-        while let Some(i) = self.iterator.next() {
+        let mut values = self.previous_value.take().into_iter().collect::<Vec<_>>();
+        for i in self.iterator.by_ref() {
             if values.is_empty() || i - values.last().unwrap() == 1 {
                 values.push(i)
             } else {
@@ -97,7 +189,11 @@ where
                 return Some(values);
             }
         }
-        None
+        if values.is_empty() {
+            None
+        } else {
+            Some(values)
+        }
     }
 }
 
@@ -120,6 +216,40 @@ where
     }
 }
 
+/// Runs of at most 5 consecutive joltages separated by differences of 3, like the personal
+/// inputs. Runs of 1 or 2 joltages, which have a single arrangement, take over once the number of
+/// arrangements gets close to overflowing.
+fn generate_adapters(random: &mut Random, size: usize) -> Generated {
+    const ARRANGEMENTS_OF_RUN: [u64; 5] = [1, 1, 2, 4, 7];
+    let size = size.max(1);
+    let mut adapters = Vec::with_capacity(size);
+    // The outlet starts the first run, and the device is 3 jolts above the last adapter
+    let (mut differences_of_1, mut differences_of_3, mut arrangements) = (0, 1, 1);
+    let mut start = 0;
+    while adapters.len() < size {
+        let remaining = (size - adapters.len() + if start == 0 { 1 } else { 0 }) as u64;
+        let longest = if arrangements < 1 << 58 { 5 } else { 2 };
+        let length = random.between(1, longest.min(remaining));
+        adapters.extend((start..start + length).filter(|joltage| *joltage > 0));
+        differences_of_1 += length - 1;
+        arrangements *= ARRANGEMENTS_OF_RUN[length as usize - 1];
+        if start > 0 {
+            differences_of_3 += 1;
+        }
+        start += length + 2;
+    }
+    random.shuffle(&mut adapters);
+    Generated {
+        input: adapters
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        part1: Some((differences_of_1 * differences_of_3) as i128),
+        part2: Some(arrangements as i128),
+    }
+}
+
 #[cfg(test)]
 mod compute_adapter_chain_should {
     use super::*;
@@ -154,32 +284,66 @@ mod count_jolt_differences_should {
     }
 }
 
+#[cfg(test)]
+mod total_arrangements_should {
+    use super::*;
+
+    #[test]
+    fn return_8_for_the_first_sample() {
+        let adapters = [0, 16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4]
+            .iter()
+            .copied()
+            .collect::<HashSet<_>>();
+
+        assert_eq!(total_arrangements(&adapters), Some(8));
+    }
+}
+
 #[cfg(test)]
 mod nb_combinations_should {
     use super::*;
 
     #[test]
     fn return_1_for_1() {
-        assert_eq!(nb_combinations(1), 1);
+        assert_eq!(nb_combinations(1), Some(1));
     }
 
     #[test]
     fn return_1_for_2() {
-        assert_eq!(nb_combinations(2), 1);
+        assert_eq!(nb_combinations(2), Some(1));
     }
 
     #[test]
     fn return_2_for_3() {
-        assert_eq!(nb_combinations(3), 2);
+        assert_eq!(nb_combinations(3), Some(2));
     }
 
     #[test]
     fn return_4_for_4() {
-        assert_eq!(nb_combinations(4), 4);
+        assert_eq!(nb_combinations(4), Some(4));
     }
 
     #[test]
     fn return_7_for_5() {
-        assert_eq!(nb_combinations(5), 7);
+        assert_eq!(nb_combinations(5), Some(7));
+    }
+
+    #[test]
+    fn return_nothing_when_the_number_overflows() {
+        assert_eq!(nb_combinations(100), None);
+    }
+}
+
+#[cfg(test)]
+mod part2_should {
+    use super::*;
+
+    #[test]
+    fn fail_when_the_number_of_arrangements_overflows() {
+        let adapters = (0..40)
+            .flat_map(|run| (0..5).map(move |i| run * 7 + i))
+            .collect::<HashSet<_>>();
+
+        assert!(Day10.part2(&adapters).is_err());
     }
 }
@@ -1,234 +1,371 @@
+use std::cell::OnceCell;
 use std::fmt::{Display, Formatter};
 
-use itertools::Itertools;
-
+use crate::error::ParseError;
+use crate::geometry::{CompassDirection, Vector};
+use crate::grid::{Cell, Grid};
+use crate::iterate::{find_cycle, Outcome};
 use crate::puzzle::day11::Space::{AvailableSeat, Floor, OccupiedSeat};
-use crate::puzzle::input::read_input;
-use itertools::__std_iter::successors;
-
-pub fn execute() {
-    let area = WaitingArea::from(read_input("day11").as_str());
-    println!(
-        "11:1 — Number of occupied seats after stabilization: {}",
-        compute_stable_area(&area).nb_occupied_seats()
-    );
-    println!(
-        "11:1 — Number of occupied seats after stabilization with second part rules: {}",
-        compute_stable_area_part2(&area).nb_occupied_seats()
-    );
-}
+use crate::puzzle::{Answer, Example, Generated, Solution, Visual};
+use crate::random::Random;
+use crate::repl::{ReplError, Session};
+use crate::trace::trace;
+use std::convert::TryFrom;
 
-fn compute_stable_area(area: &WaitingArea) -> WaitingArea {
-    let mut current_area = area.next_round();
-    loop {
-        let new_area = current_area.next_round();
-        if new_area == current_area {
-            break current_area;
-        }
-        current_area = new_area;
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Model = Seats;
+
+    fn day(&self) -> u8 {
+        11
+    }
+
+    fn title(&self) -> &'static str {
+        "Seating System"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Number of occupied seats after stabilization",
+            "Number of occupied seats after stabilization with second part rules",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Seats, ParseError> {
+        WaitingArea::try_from(input).map(Seats::new)
+    }
+
+    fn part1(&self, seats: &Seats) -> Result<Answer, ParseError> {
+        Ok(seats.stable_area()?.nb_occupied_seats().into())
+    }
+
+    fn part2(&self, seats: &Seats) -> Result<Answer, ParseError> {
+        Ok(seats.stable_area_part2()?.nb_occupied_seats().into())
+    }
+
+    fn session(&self, seats: Seats) -> Option<Box<dyn Session>> {
+        Some(Box::new(Simulation::new(seats.area)))
+    }
+
+    fn visuals(&self, seats: &Seats) -> Vec<Visual> {
+        let stable_areas = [
+            ("Stable area with adjacent seats rules", seats.stable_area()),
+            (
+                "Stable area with visible seats rules",
+                seats.stable_area_part2(),
+            ),
+        ];
+        stable_areas
+            .iter()
+            .filter_map(|(title, stable_area)| {
+                let picture = stable_area.as_ref().ok()?.to_string();
+                Some(Visual { title, picture })
+            })
+            .collect()
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_area(random, size))
     }
 }
 
-fn compute_stable_area_part2(area: &WaitingArea) -> WaitingArea {
-    let mut current_area = area.next_round_part2();
-    loop {
-        let new_area = current_area.next_round_part2();
-        if new_area == current_area {
-            break current_area;
+static EXAMPLES: &[Example] = &[Example {
+    input: r"L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL",
+    part1: Some(37),
+    part2: Some(26),
+    solver: None,
+}];
+
+fn compute_stable_area(area: &WaitingArea) -> Result<WaitingArea, ParseError> {
+    stabilize(area, WaitingArea::next_round)
+}
+
+fn compute_stable_area_part2(area: &WaitingArea) -> Result<WaitingArea, ParseError> {
+    stabilize(area, WaitingArea::next_round_part2)
+}
+
+/// Some layouts never settle: their seats keep being taken and left every other round
+fn stabilize(
+    area: &WaitingArea,
+    next_round: fn(&WaitingArea) -> WaitingArea,
+) -> Result<WaitingArea, ParseError> {
+    let iteration = find_cycle(area.clone(), next_round, None);
+    if let Outcome::Cycle { period } = iteration.outcome {
+        return Err(ParseError::new(
+            "",
+            format!(
+                "the seats never stabilize, they repeat every {} rounds",
+                period
+            ),
+        ));
+    }
+    trace!(Info, "stable after {} rounds", iteration.steps);
+    Ok(iteration.state)
+}
+
+/// The parsed area and its stable areas once computed, which the report pictures after the parts
+/// counted their seats
+pub struct Seats {
+    area: WaitingArea,
+    stable_area: OnceCell<Result<WaitingArea, ParseError>>,
+    stable_area_part2: OnceCell<Result<WaitingArea, ParseError>>,
+}
+
+impl Seats {
+    fn new(area: WaitingArea) -> Self {
+        Self {
+            area,
+            stable_area: OnceCell::new(),
+            stable_area_part2: OnceCell::new(),
         }
-        current_area = new_area;
+    }
+
+    fn stable_area(&self) -> Result<&WaitingArea, ParseError> {
+        self.stable_area
+            .get_or_init(|| compute_stable_area(&self.area))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    fn stable_area_part2(&self) -> Result<&WaitingArea, ParseError> {
+        self.stable_area_part2
+            .get_or_init(|| compute_stable_area_part2(&self.area))
+            .as_ref()
+            .map_err(Clone::clone)
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 enum Space {
     Floor,
     AvailableSeat,
     OccupiedSeat,
 }
 
-type Row = Vec<Space>;
+impl Cell for Space {
+    const CHARS: &'static [(char, Self)] =
+        &[('.', Floor), ('L', AvailableSeat), ('#', OccupiedSeat)];
+}
 
-#[derive(PartialEq)]
-struct WaitingArea {
-    rows: Vec<Row>,
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct WaitingArea {
+    grid: Grid<Space>,
 }
 
 impl WaitingArea {
     fn next_round(&self) -> Self {
-        Self {
-            rows: self
-                .rows
-                .iter()
-                .enumerate()
-                .map(|(r, row)| {
-                    row.iter()
-                        .enumerate()
-                        .map(
-                            |(c, space)| match (space, self.nb_occupied_adjacent_seats(r, c)) {
-                                (AvailableSeat, 0) => OccupiedSeat,
-                                (OccupiedSeat, n) if n >= 4 => AvailableSeat,
-                                _ => *space,
-                            },
-                        )
-                        .collect()
-                })
-                .collect(),
-        }
+        self.play_round(4, |position| self.nb_occupied_adjacent_seats(position))
     }
 
     fn next_round_part2(&self) -> Self {
-        Self {
-            rows: self
-                .rows
-                .iter()
-                .enumerate()
-                .map(|(r, row)| {
-                    row.iter()
-                        .enumerate()
-                        .map(
-                            |(c, space)| match (space, self.nb_visible_occupied_seats(r, c)) {
-                                (AvailableSeat, 0) => OccupiedSeat,
-                                (OccupiedSeat, n) if n >= 5 => AvailableSeat,
-                                _ => *space,
-                            },
-                        )
-                        .collect()
-                })
-                .collect(),
-        }
+        self.play_round(5, |position| self.nb_visible_occupied_seats(position))
     }
 
-    fn get_space(&self, row: usize, column: usize) -> Option<&Space> {
-        self.rows.get(row).and_then(|row| row.get(column))
-    }
-
-    fn is_occupied(&self, row: i32, column: i32) -> bool {
-        if row < 0 || column < 0 {
-            false
-        } else {
-            matches!(
-                self.get_space(row as usize, column as usize),
-                Some(&Space::OccupiedSeat)
-            )
+    /// An occupied seat is left when at least `tolerance` other seats are occupied
+    fn play_round(&self, tolerance: usize, nb_occupied: impl Fn(Vector) -> usize) -> Self {
+        Self {
+            grid: self.grid.map(|position, space| match space {
+                AvailableSeat if nb_occupied(position) == 0 => OccupiedSeat,
+                OccupiedSeat if nb_occupied(position) >= tolerance => AvailableSeat,
+                _ => *space,
+            }),
         }
     }
 
-    fn nb_occupied_adjacent_seats(&self, row: usize, column: usize) -> usize {
-        let row = row as i32;
-        let column = column as i32;
-        (row - 1..=row + 1)
-            .cartesian_product(column - 1..=column + 1)
-            .filter(|(r, c)| (*r, *c) != (row, column) && self.is_occupied(*r, *c))
+    fn nb_occupied_adjacent_seats(&self, position: Vector) -> usize {
+        self.grid
+            .neighbours8(position)
+            .filter(|space| **space == OccupiedSeat)
             .count()
     }
 
-    fn has_visible_occupied_seat_in_slope(
-        &self,
-        row: usize,
-        column: usize,
-        slope: (i32, i32),
-    ) -> bool {
-        successors(Some((row as i32, column as i32)), |(r, c)| {
-            let next = (r + slope.0, c + slope.1);
-            if self.is_in_area(next) {
-                Some(next)
-            } else {
-                None
-            }
-        })
-        .skip(1)
-        .flat_map(|(r, c)| self.get_space(r as usize, c as usize))
-        .find(|space| **space != Floor)
-        .map(|space| *space == OccupiedSeat)
-        .unwrap_or(false)
-    }
-
-    fn is_in_area(&self, (row, column): (i32, i32)) -> bool {
-        row >= 0
-            && column >= 0
-            && (row as usize) < self.rows.len()
-            && (column as usize) < self.rows.get(0).map(|c| c.len()).unwrap_or(0)
-    }
-
-    fn nb_visible_occupied_seats(&self, row: usize, column: usize) -> usize {
-        (-1..=1)
-            .cartesian_product(-1..=1)
-            .filter(|slope| *slope != (0, 0))
-            .filter(|slope| self.has_visible_occupied_seat_in_slope(row, column, *slope))
+    fn nb_visible_occupied_seats(&self, position: Vector) -> usize {
+        CompassDirection::ALL
+            .iter()
+            .filter(|direction| {
+                self.grid
+                    .ray(position, **direction)
+                    .find(|space| **space != Floor)
+                    == Some(&OccupiedSeat)
+            })
             .count()
     }
 
     fn nb_occupied_seats(&self) -> usize {
-        self.rows
-            .iter()
-            .flat_map(|row| row.iter())
-            .filter(|s| **s == OccupiedSeat)
+        self.grid
+            .cells()
+            .filter(|space| **space == OccupiedSeat)
             .count()
     }
 }
 
-impl From<&str> for WaitingArea {
-    fn from(lines: &str) -> Self {
-        let rows = lines
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| {
-                line.chars()
-                    .map(|c| match c {
-                        'L' => Space::AvailableSeat,
-                        '#' => Space::OccupiedSeat,
-                        _ => Space::Floor,
-                    })
-                    .collect()
-            })
-            .collect();
-        Self { rows }
+struct Simulation {
+    initial_area: WaitingArea,
+    area: WaitingArea,
+    round: usize,
+}
+
+impl Simulation {
+    fn new(area: WaitingArea) -> Self {
+        Self {
+            initial_area: area.clone(),
+            area,
+            round: 0,
+        }
+    }
+
+    fn state(&self) -> String {
+        format!(
+            "Round {}, {} occupied seats\n{}",
+            self.round,
+            self.area.nb_occupied_seats(),
+            self.area
+        )
     }
 }
 
-impl Display for WaitingArea {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for row in &self.rows {
-            for space in row {
-                match space {
-                    Space::Floor => write!(f, ".")?,
-                    Space::AvailableSeat => write!(f, "L")?,
-                    Space::OccupiedSeat => write!(f, "#")?,
-                }
+impl Session for Simulation {
+    fn namespace(&self) -> &'static str {
+        "area"
+    }
+
+    fn commands(&self) -> &'static [&'static str] {
+        &[
+            "step [adjacent|visible] — play a round with the part 1 or part 2 rules",
+            "show — print the current area",
+            "reset — go back to the parsed area",
+        ]
+    }
+
+    fn execute(&mut self, command: &str, arguments: &[String]) -> Result<String, ReplError> {
+        match command {
+            "step" => {
+                self.area = match arguments.first().map(String::as_str) {
+                    None | Some("adjacent") => self.area.next_round(),
+                    Some("visible") => self.area.next_round_part2(),
+                    Some(rules) => return Err(ReplError::InvalidArgument(rules.into())),
+                };
+                self.round += 1;
             }
-            writeln!(f)?;
+            "show" => {}
+            "reset" => {
+                self.area = self.initial_area.clone();
+                self.round = 0;
+            }
+            _ => return Err(ReplError::UnknownCommand(command.into())),
         }
-        Ok(())
+        Ok(self.state())
     }
 }
 
-#[cfg(test)]
-mod waiting_area_is_occupied_should {
-    use super::*;
+impl TryFrom<&str> for WaitingArea {
+    type Error = ParseError;
 
-    #[test]
-    fn return_true_when_space_is_occupied() {
-        let area = WaitingArea::from("#");
-        assert!(area.is_occupied(0, 0))
+    fn try_from(lines: &str) -> Result<Self, Self::Error> {
+        Grid::parse(lines).map(|grid| Self { grid })
     }
+}
 
-    #[test]
-    fn return_false_when_space_is_a_floor() {
-        let area = WaitingArea::from(".");
-        assert!(!area.is_occupied(0, 0))
+impl Display for WaitingArea {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.grid)
     }
+}
 
-    #[test]
-    fn return_false_when_space_is_available() {
-        let area = WaitingArea::from("L");
-        assert!(!area.is_occupied(0, 0))
+/// A square of about `size` spaces, three quarters of them seats at first. Like any symmetric threshold
+/// network, the area ends up either stable or alternating between two states, so the seats that
+/// still alternate become floor until the area stabilizes with both rules. The rounds are played on
+/// the seats alone rather than with the waiting area, so that the answers check it.
+fn generate_area(random: &mut Random, size: usize) -> Generated {
+    let side = size.isqrt().max(1);
+    let mut seats = (0..side * side)
+        .map(|_| random.chance(3, 4))
+        .collect::<Vec<_>>();
+    loop {
+        match (settle(&seats, side, 4, 1), settle(&seats, side, 5, side)) {
+            (Ok(part1), Ok(part2)) => {
+                let input = seats
+                    .chunks(side)
+                    .map(|row| {
+                        row.iter()
+                            .map(|seat| if *seat { AvailableSeat } else { Floor }.to_char())
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                return Generated {
+                    input,
+                    part1: Some(part1 as i128),
+                    part2: Some(part2 as i128),
+                };
+            }
+            (adjacent, visible) => {
+                for seat in adjacent.err().into_iter().chain(visible.err()).flatten() {
+                    seats[seat] = false;
+                }
+            }
+        }
     }
+}
 
-    #[test]
-    fn return_false_when_space_is_out_of_bounds() {
-        let area = WaitingArea::from("#");
-        assert!(!area.is_occupied(-1, 0))
+/// Plays rounds where a seat is left when at least `tolerance` of the first seats within `reach`
+/// in each direction are occupied. Returns the number of occupied seats once they settle, or the
+/// indexes of the seats that keep changing once they alternate.
+fn settle(
+    seats: &[bool],
+    side: usize,
+    tolerance: usize,
+    reach: usize,
+) -> Result<usize, Vec<usize>> {
+    let side = side as isize;
+    let (mut previous, mut occupied) = (None, vec![false; seats.len()]);
+    loop {
+        let next = (0..seats.len())
+            .map(|i| {
+                let (x, y) = (i as isize % side, i as isize / side);
+                let nb_occupied = (-1..=1)
+                    .flat_map(|dx| (-1..=1).map(move |dy| (dx, dy)))
+                    .filter(|step| *step != (0, 0))
+                    .filter(|(dx, dy)| {
+                        (1..=reach as isize)
+                            .map(|k| (x + dx * k, y + dy * k))
+                            .take_while(|(x, y)| (0..side).contains(x) && (0..side).contains(y))
+                            .map(|(x, y)| (y * side + x) as usize)
+                            .find(|j| seats[*j])
+                            .is_some_and(|j| occupied[j])
+                    })
+                    .count();
+                seats[i]
+                    && if occupied[i] {
+                        nb_occupied < tolerance
+                    } else {
+                        nb_occupied == 0
+                    }
+            })
+            .collect::<Vec<_>>();
+        if next == occupied {
+            return Ok(occupied.iter().filter(|seat| **seat).count());
+        }
+        if previous.as_ref() == Some(&next) {
+            return Err((0..seats.len())
+                .filter(|i| next[*i] != occupied[*i])
+                .collect());
+        }
+        previous = Some(std::mem::replace(&mut occupied, next));
     }
 }
 
@@ -238,166 +375,180 @@ mod nb_occupied_adjacent_seats_should {
 
     #[test]
     fn return_0_when_there_are_no_adjacent_occupied_seats_and_current_seat_is_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 LLL
 L#L
 LLL",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 1), 0);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 1)), 0);
     }
 
     #[test]
     fn return_1_when_upper_seat_is_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 L#L
 LLL
 LLL",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 1), 1);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 1)), 1);
     }
 
     #[test]
     fn return_1_when_left_seat_is_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 LLL
 #LL
 LLL",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 1), 1);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 1)), 1);
     }
 
     #[test]
     fn return_1_when_right_seat_is_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 LLL
 LL#
 LLL",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 1), 1);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 1)), 1);
     }
 
     #[test]
     fn return_1_when_lower_seat_is_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 LLL
 LLL
 L#L",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 1), 1);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 1)), 1);
     }
 
     #[test]
     fn return_1_when_upper_left_seat_is_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 #LL
 LLL
 LLL",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 1), 1);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 1)), 1);
     }
 
     #[test]
     fn return_1_when_upper_right_seat_is_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 LL#
 LLL
 LLL",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 1), 1);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 1)), 1);
     }
 
     #[test]
     fn return_1_when_lower_left_seat_is_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 LLL
 LLL
 #LL",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 1), 1);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 1)), 1);
     }
 
     #[test]
     fn return_1_when_lower_right_seat_is_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 LLL
 LLL
 LL#",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 1), 1);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 1)), 1);
     }
 
     #[test]
     fn return_7_when_there_are_seven_adjacent_spaces() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 ###
 L##
 ###",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 1), 7);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 1)), 7);
     }
 
     #[test]
     fn return_3_when_current_space_is_in_upper_left_corner_and_all_seats_are_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 ##
 ##",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(0, 0), 3);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(0, 0)), 3);
     }
 
     #[test]
     fn return_3_when_current_space_is_in_upper_right_corner_and_all_seats_are_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 ##
 ##",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(0, 1), 3);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 0)), 3);
     }
 
     #[test]
     fn return_3_when_current_space_is_in_lower_right_corner_and_all_seats_are_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 ##
 ##",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 1), 3);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(1, 1)), 3);
     }
 
     #[test]
     fn return_3_when_current_space_is_in_lower_left_corner_and_all_seats_are_occupied() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 ##
 ##",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_occupied_adjacent_seats(1, 0), 3);
+        assert_eq!(area.nb_occupied_adjacent_seats(Vector::new(0, 1)), 3);
     }
 }
 
@@ -407,7 +558,7 @@ mod next_round_test {
 
     #[test]
     fn first_round_of_example() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 L.LL.LL.LL
 LLLLLLL.LL
@@ -420,7 +571,8 @@ LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL
 ",
-        );
+        )
+        .unwrap();
 
         let next = area.next_round();
 
@@ -442,7 +594,7 @@ L.LLLLL.LL
 
     #[test]
     fn second_round_of_example() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 #.##.##.##
 #######.##
@@ -455,7 +607,8 @@ L.LLLLL.LL
 #.######.#
 #.#####.##
 ",
-        );
+        )
+        .unwrap();
 
         let next = area.next_round();
 
@@ -477,7 +630,7 @@ L.L.L..L..
 
     #[test]
     fn third_round_of_example() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 #.LL.L#.##
 #LLLLLL.L#
@@ -490,7 +643,8 @@ L.L.L..L..
 #.LLLLLL.L
 #.#LLLL.##
 ",
-        );
+        )
+        .unwrap();
 
         let next = area.next_round();
 
@@ -517,7 +671,7 @@ mod compute_stable_area_test {
 
     #[test]
     fn example() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 L.LL.LL.LL
 LLLLLLL.LL
@@ -530,9 +684,10 @@ LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL
 ",
-        );
+        )
+        .unwrap();
 
-        let result = compute_stable_area(&area);
+        let result = compute_stable_area(&area).unwrap();
 
         assert_eq!(
             result.to_string(),
@@ -549,6 +704,19 @@ L.#.L..#..
 "
         )
     }
+
+    #[test]
+    fn fail_when_the_seats_alternate_forever() {
+        let area = WaitingArea::try_from(".LLL.\nLLLLL\nLLLLL\nLLLLL\n.LLL.").unwrap();
+
+        assert_eq!(
+            compute_stable_area(&area),
+            Err(ParseError::new(
+                "",
+                "the seats never stabilize, they repeat every 2 rounds"
+            ))
+        );
+    }
 }
 
 #[cfg(test)]
@@ -557,7 +725,7 @@ mod nb_visible_occupied_seats_should {
 
     #[test]
     fn return_8_in_first_example() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 .......#.
 ...#.....
@@ -569,27 +737,29 @@ mod nb_visible_occupied_seats_should {
 #........
 ...#.....
 ",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_visible_occupied_seats(4, 3), 8);
+        assert_eq!(area.nb_visible_occupied_seats(Vector::new(3, 4)), 8);
     }
 
     #[test]
     fn return_0_in_second_example() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 .............
 .L.L.#.#.#.#.
 .............
 ",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(area.nb_visible_occupied_seats(1, 1), 0);
+        assert_eq!(area.nb_visible_occupied_seats(Vector::new(1, 1)), 0);
     }
 
     #[test]
     fn return_0_in_third_example() {
-        let area = WaitingArea::from(
+        let area = WaitingArea::try_from(
             r"
 .##.##.
 #.#.#.#
@@ -599,8 +769,82 @@ mod nb_visible_occupied_seats_should {
 #.#.#.#
 .##.##.
 ",
+        )
+        .unwrap();
+
+        assert_eq!(area.nb_visible_occupied_seats(Vector::new(3, 3)), 0);
+    }
+}
+
+#[cfg(test)]
+mod simulation_session_should {
+    use super::*;
+    use crate::repl::run_command;
+
+    #[test]
+    fn play_a_round() {
+        let mut simulation = Simulation::new(WaitingArea::try_from("L.L\nLLL\n...").unwrap());
+
+        assert_eq!(
+            run_command(&mut simulation, "area step"),
+            Ok("Round 1, 5 occupied seats\n#.#\n###\n...\n".into())
         );
+    }
+
+    #[test]
+    fn reset_the_area() {
+        let mut simulation = Simulation::new(WaitingArea::try_from("L.L\nLLL\n...").unwrap());
+        run_command(&mut simulation, "area step visible").unwrap();
+
+        assert_eq!(
+            run_command(&mut simulation, "area reset"),
+            Ok("Round 0, 0 occupied seats\nL.L\nLLL\n...\n".into())
+        );
+    }
+}
+
+#[cfg(test)]
+mod visuals_should {
+    use super::*;
+
+    #[test]
+    fn picture_the_areas_stabilized_by_the_parts() {
+        let seats = Day11.parse(EXAMPLES[0].input).unwrap();
+        Day11.part1(&seats).unwrap();
+        Day11.part2(&seats).unwrap();
+        let stable_areas = [
+            seats.stable_area.get().cloned(),
+            seats.stable_area_part2.get().cloned(),
+        ];
+
+        let visuals = Day11.visuals(&seats);
+
+        assert_eq!(visuals.len(), 2);
+        for (visual, stable_area) in visuals.iter().zip(&stable_areas) {
+            assert_eq!(
+                Some(visual.picture.clone()),
+                stable_area
+                    .clone()
+                    .and_then(Result::ok)
+                    .map(|area| area.to_string())
+            );
+        }
+    }
+}
 
-        assert_eq!(area.nb_visible_occupied_seats(3, 3), 0);
+#[cfg(test)]
+mod generate_area_should {
+    use super::*;
+
+    #[test]
+    fn stabilize_with_both_rules() {
+        for seed in 0..5 {
+            let input = generate_area(&mut Random::new(seed), 144).input;
+            let area = WaitingArea::try_from(input.as_str()).unwrap();
+
+            assert_eq!(input.lines().count(), 12);
+            assert!(compute_stable_area(&area).is_ok());
+            assert!(compute_stable_area_part2(&area).is_ok());
+        }
     }
 }
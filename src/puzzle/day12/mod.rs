@@ -1,172 +1,228 @@
-use crate::puzzle::input::read_lines;
+use std::convert::TryFrom;
+
+use crate::error::ParseError;
+use crate::geometry::{Direction, Vector};
+use crate::puzzle::input::{parse_lines, parse_number};
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
 
 mod part2;
 
-pub fn execute() {
-    let actions = get_actions();
-    let ship = Ship::new();
-    let Ship { position, .. } = ship.execute_all(&actions);
-    println!(
-        "12:1 — Manhattan distance from origin after executing all actions: {}",
-        position.manhattan_distance_from_origin(),
-    );
-    part2::execute(&actions);
-}
+pub struct Day12;
 
-fn get_actions() -> Vec<Action> {
-    read_lines("day12")
-        .unwrap()
-        .iter()
-        .map(|line| line.as_str().into())
-        .collect()
-}
+impl Solution for Day12 {
+    type Model = Vec<Action>;
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-enum Direction {
-    North,
-    South,
-    West,
-    East,
-}
+    fn day(&self) -> u8 {
+        12
+    }
 
-impl Direction {
-    fn turn(&self, angle: i32) -> Self {
-        match (self.value() + angle / 90) % 4 {
-            0 => Direction::North,
-            1 | -3 => Direction::East,
-            2 | -2 => Direction::South,
-            _ => Direction::West,
-        }
+    fn title(&self) -> &'static str {
+        "Rain Risk"
     }
 
-    fn value(&self) -> i32 {
-        match self {
-            Direction::North => 0,
-            Direction::East => 1,
-            Direction::South => 2,
-            Direction::West => 3,
-        }
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Manhattan distance from origin after executing all actions",
+            "Manhattan distance from origin after executing all actions with a waypoint",
+        ]
     }
-}
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-struct Position {
-    x: i32,
-    y: i32,
-}
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
 
-impl Position {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
+    fn parse(&self, input: &str) -> Result<Vec<Action>, ParseError> {
+        get_actions(input)
     }
 
-    fn move_forward(&self, direction: Direction, distance: i32) -> Self {
-        match direction {
-            Direction::East => Self {
-                x: self.x + distance,
-                ..*self
-            },
-            Direction::South => Self {
-                y: self.y - distance,
-                ..*self
-            },
-            Direction::West => Self {
-                x: self.x - distance,
-                ..*self
-            },
-            Direction::North => Self {
-                y: self.y + distance,
-                ..*self
-            },
-        }
+    fn part1(&self, actions: &Vec<Action>) -> Result<Answer, ParseError> {
+        let Ship { position, .. } = Ship::new()
+            .execute_all(actions)
+            .ok_or_else(beyond_coordinates)?;
+        Ok(distance_from_origin(position))
     }
 
-    fn manhattan_distance_from_origin(&self) -> u32 {
-        (self.x.abs() + self.y.abs()) as u32
+    fn part2(&self, actions: &Vec<Action>) -> Result<Answer, ParseError> {
+        part2::position_after_all_actions(actions)
+            .map(distance_from_origin)
+            .ok_or_else(beyond_coordinates)
     }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_actions(random, size))
+    }
+}
+
+static EXAMPLES: &[Example] = &[Example {
+    input: r"F10
+N3
+F7
+R90
+F11",
+    part1: Some(25),
+    part2: Some(286),
+    solver: None,
+}];
+
+fn get_actions(input: &str) -> Result<Vec<Action>, ParseError> {
+    parse_lines(input, Action::try_from)
+}
+
+fn beyond_coordinates() -> ParseError {
+    ParseError::new(
+        "",
+        "the ship or its waypoint goes beyond 64-bit coordinates",
+    )
+}
+
+/// Both coordinates can be as far as 2^63, so their sum is only taken in 128 bits
+fn distance_from_origin(position: Vector) -> Answer {
+    Answer::from(position.x.unsigned_abs() as i128 + position.y.unsigned_abs() as i128)
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
 struct Ship {
     direction: Direction,
-    position: Position,
+    position: Vector,
 }
 
 impl Ship {
     fn new() -> Self {
         Self {
             direction: Direction::East,
-            position: Position::new(0, 0),
+            position: Vector::ORIGIN,
         }
     }
 
-    fn execute(&self, action: Action) -> Self {
-        match action {
-            Action::Forward(distance) => Self {
-                position: self.position.move_forward(self.direction, distance),
-                ..*self
-            },
-            Action::East(distance) => Self {
-                position: self.position.move_forward(Direction::East, distance),
-                ..*self
-            },
-            Action::South(distance) => Self {
-                position: self.position.move_forward(Direction::South, distance),
-                ..*self
-            },
-            Action::West(distance) => Self {
-                position: self.position.move_forward(Direction::West, distance),
-                ..*self
-            },
-            Action::North(distance) => Self {
-                position: self.position.move_forward(Direction::North, distance),
-                ..*self
-            },
+    /// None when the ship sails beyond 64-bit coordinates
+    fn execute(&self, action: Action) -> Option<Self> {
+        Some(match action {
+            Action::Forward(distance) => self.sail(self.direction, distance)?,
+            Action::East(distance) => self.sail(Direction::East, distance)?,
+            Action::South(distance) => self.sail(Direction::South, distance)?,
+            Action::West(distance) => self.sail(Direction::West, distance)?,
+            Action::North(distance) => self.sail(Direction::North, distance)?,
             Action::Right(angle) => Self {
-                direction: self.direction.turn(angle),
+                direction: self.direction.turn(angle / 90),
                 ..*self
             },
             Action::Left(angle) => Self {
-                direction: self.direction.turn(-angle),
+                direction: self.direction.turn(-angle / 90),
                 ..*self
             },
-        }
+        })
+    }
+
+    fn sail(&self, direction: Direction, distance: isize) -> Option<Self> {
+        Some(Self {
+            position: self
+                .position
+                .checked_add(direction.vector().checked_mul(distance)?)?,
+            ..*self
+        })
     }
 
-    fn execute_all(&self, actions: &[Action]) -> Ship {
+    fn execute_all(&self, actions: &[Action]) -> Option<Ship> {
         actions
             .iter()
-            .fold(*self, |ship, action| ship.execute(*action))
+            .try_fold(*self, |ship, action| ship.execute(*action))
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Action {
-    North(i32),
-    South(i32),
-    West(i32),
-    East(i32),
-    Left(i32),
-    Right(i32),
-    Forward(i32),
+    North(isize),
+    South(isize),
+    West(isize),
+    East(isize),
+    Left(isize),
+    Right(isize),
+    Forward(isize),
 }
 
-impl From<&str> for Action {
-    fn from(action: &str) -> Self {
-        let value = action[1..].parse().unwrap();
-        match &action[0..1] {
-            "N" => Action::North(value),
-            "S" => Action::South(value),
-            "W" => Action::West(value),
-            "E" => Action::East(value),
-            "L" => Action::Left(value),
-            "R" => Action::Right(value),
-            "F" => Action::Forward(value),
-            c => panic!("Unknown action {}", c),
+impl TryFrom<&str> for Action {
+    type Error = ParseError;
+
+    fn try_from(action: &str) -> Result<Self, Self::Error> {
+        let mut chars = action.chars();
+        let kind = chars.next();
+        let value = parse_number(chars.as_str())
+            .map_err(|_| ParseError::new(action, "expected an action followed by a number"))?;
+        if matches!(kind, Some('L') | Some('R')) && value % 90 != 0 {
+            return Err(ParseError::new(action, "expected a multiple of 90 degrees"));
+        }
+        match kind {
+            Some('N') => Ok(Action::North(value)),
+            Some('S') => Ok(Action::South(value)),
+            Some('W') => Ok(Action::West(value)),
+            Some('E') => Ok(Action::East(value)),
+            Some('L') => Ok(Action::Left(value)),
+            Some('R') => Ok(Action::Right(value)),
+            Some('F') => Ok(Action::Forward(value)),
+            _ => Err(ParseError::new(
+                action,
+                "unknown action (N, S, E, W, L, R or F)",
+            )),
         }
     }
 }
 
+/// Turns are a multiple of 90 degrees, like the parsing requires. Both ships are followed with
+/// plain coordinates while the actions are drawn, y going south.
+fn generate_actions(random: &mut Random, size: usize) -> Generated {
+    // East, south, west and north, in clockwise order
+    const STEPS: [(i128, i128); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+    let (mut ship, mut heading) = ((0, 0), 0);
+    let (mut other_ship, mut waypoint) = ((0, 0), (10, -1));
+    let mut actions = Vec::with_capacity(size);
+    for _ in 0..size {
+        let kind = *random.choose(&['N', 'S', 'E', 'W', 'L', 'R', 'F']);
+        let value = match kind {
+            'L' | 'R' => 90 * random.between(1, 3),
+            _ => random.between(1, 100),
+        };
+        actions.push(format!("{}{}", kind, value));
+        let value = value as i128;
+        let (dx, dy) = match kind {
+            'E' => STEPS[0],
+            'S' => STEPS[1],
+            'W' => STEPS[2],
+            'N' => STEPS[3],
+            'F' => {
+                ship = (
+                    ship.0 + STEPS[heading].0 * value,
+                    ship.1 + STEPS[heading].1 * value,
+                );
+                other_ship = (
+                    other_ship.0 + waypoint.0 * value,
+                    other_ship.1 + waypoint.1 * value,
+                );
+                continue;
+            }
+            turn => {
+                let quarter_turns = if turn == 'R' {
+                    value / 90
+                } else {
+                    4 - value / 90
+                };
+                for _ in 0..quarter_turns {
+                    heading = (heading + 1) % 4;
+                    waypoint = (-waypoint.1, waypoint.0);
+                }
+                continue;
+            }
+        };
+        ship = (ship.0 + dx * value, ship.1 + dy * value);
+        waypoint = (waypoint.0 + dx * value, waypoint.1 + dy * value);
+    }
+    Generated {
+        input: actions.join("\n"),
+        part1: Some(ship.0.abs() + ship.1.abs()),
+        part2: Some(other_ship.0.abs() + other_ship.1.abs()),
+    }
+}
+
 #[cfg(test)]
 mod ship_execute_should {
     use super::*;
@@ -175,16 +231,16 @@ mod ship_execute_should {
     fn move_the_ship_10_towards_east_when_it_is_facing_east_and_action_is_forward_10() {
         let ship = Ship {
             direction: Direction::East,
-            position: Position::new(0, 0),
+            position: Vector::new(0, 0),
         };
 
-        let result = ship.execute(Action::Forward(10));
+        let result = ship.execute(Action::Forward(10)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::East,
-                position: Position::new(10, 0)
+                position: Vector::new(10, 0)
             }
         );
     }
@@ -193,16 +249,16 @@ mod ship_execute_should {
     fn move_the_ship_10_towards_south_when_it_is_facing_south_and_action_is_forward_10() {
         let ship = Ship {
             direction: Direction::South,
-            position: Position::new(0, 0),
+            position: Vector::new(0, 0),
         };
 
-        let result = ship.execute(Action::Forward(10));
+        let result = ship.execute(Action::Forward(10)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::South,
-                position: Position::new(0, -10)
+                position: Vector::new(0, 10)
             }
         );
     }
@@ -211,16 +267,16 @@ mod ship_execute_should {
     fn move_the_ship_10_towards_west_when_it_is_facing_west_and_action_is_forward_10() {
         let ship = Ship {
             direction: Direction::West,
-            position: Position::new(0, 0),
+            position: Vector::new(0, 0),
         };
 
-        let result = ship.execute(Action::Forward(10));
+        let result = ship.execute(Action::Forward(10)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::West,
-                position: Position::new(-10, 0)
+                position: Vector::new(-10, 0)
             }
         );
     }
@@ -229,16 +285,16 @@ mod ship_execute_should {
     fn move_the_ship_10_towards_north_when_it_is_facing_north_and_action_is_forward_10() {
         let ship = Ship {
             direction: Direction::North,
-            position: Position::new(0, 0),
+            position: Vector::new(0, 0),
         };
 
-        let result = ship.execute(Action::Forward(10));
+        let result = ship.execute(Action::Forward(10)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::North,
-                position: Position::new(0, 10)
+                position: Vector::new(0, -10)
             }
         );
     }
@@ -247,16 +303,16 @@ mod ship_execute_should {
     fn move_the_ship_10_towards_east_when_action_is_east_10() {
         let ship = Ship {
             direction: Direction::West,
-            position: Position::new(0, 0),
+            position: Vector::new(0, 0),
         };
 
-        let result = ship.execute(Action::East(10));
+        let result = ship.execute(Action::East(10)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::West,
-                position: Position::new(10, 0)
+                position: Vector::new(10, 0)
             }
         );
     }
@@ -265,13 +321,13 @@ mod ship_execute_should {
     fn move_the_ship_10_towards_south_when_action_is_south_10() {
         let ship = Ship::new();
 
-        let result = ship.execute(Action::South(10));
+        let result = ship.execute(Action::South(10)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::East,
-                position: Position::new(0, -10)
+                position: Vector::new(0, 10)
             }
         );
     }
@@ -280,13 +336,13 @@ mod ship_execute_should {
     fn move_the_ship_10_towards_west_when_action_is_west_10() {
         let ship = Ship::new();
 
-        let result = ship.execute(Action::West(10));
+        let result = ship.execute(Action::West(10)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::East,
-                position: Position::new(-10, 0)
+                position: Vector::new(-10, 0)
             }
         );
     }
@@ -295,13 +351,13 @@ mod ship_execute_should {
     fn move_the_ship_10_towards_north_when_action_is_north_10() {
         let ship = Ship::new();
 
-        let result = ship.execute(Action::North(10));
+        let result = ship.execute(Action::North(10)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::East,
-                position: Position::new(0, 10)
+                position: Vector::new(0, -10)
             }
         );
     }
@@ -310,16 +366,16 @@ mod ship_execute_should {
     fn turn_the_ship_towards_south_when_it_is_facing_east_and_action_is_right_90() {
         let ship = Ship {
             direction: Direction::East,
-            position: Position::new(0, 0),
+            position: Vector::new(0, 0),
         };
 
-        let result = ship.execute(Action::Right(90));
+        let result = ship.execute(Action::Right(90)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::South,
-                position: Position::new(0, 0)
+                position: Vector::new(0, 0)
             }
         );
     }
@@ -328,16 +384,16 @@ mod ship_execute_should {
     fn turn_the_ship_towards_north_when_it_is_facing_east_and_action_is_right_270() {
         let ship = Ship {
             direction: Direction::East,
-            position: Position::new(0, 0),
+            position: Vector::new(0, 0),
         };
 
-        let result = ship.execute(Action::Right(270));
+        let result = ship.execute(Action::Right(270)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::North,
-                position: Position::new(0, 0)
+                position: Vector::new(0, 0)
             }
         );
     }
@@ -346,16 +402,16 @@ mod ship_execute_should {
     fn turn_the_ship_towards_north_when_it_is_facing_east_and_action_is_left_90() {
         let ship = Ship {
             direction: Direction::East,
-            position: Position::new(0, 0),
+            position: Vector::new(0, 0),
         };
 
-        let result = ship.execute(Action::Left(90));
+        let result = ship.execute(Action::Left(90)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::North,
-                position: Position::new(0, 0)
+                position: Vector::new(0, 0)
             }
         );
     }
@@ -364,17 +420,65 @@ mod ship_execute_should {
     fn turn_the_ship_towards_south_when_it_is_facing_east_and_action_is_left_270() {
         let ship = Ship {
             direction: Direction::East,
-            position: Position::new(0, 0),
+            position: Vector::new(0, 0),
         };
 
-        let result = ship.execute(Action::Left(270));
+        let result = ship.execute(Action::Left(270)).unwrap();
 
         assert_eq!(
             result,
             Ship {
                 direction: Direction::South,
-                position: Position::new(0, 0)
+                position: Vector::new(0, 0)
             }
         );
     }
 }
+
+#[cfg(test)]
+mod action_should {
+    use super::*;
+
+    #[test]
+    fn parse_an_action_with_its_value() {
+        assert_eq!(Action::try_from("F10"), Ok(Action::Forward(10)));
+    }
+
+    #[test]
+    fn reject_an_unknown_action() {
+        assert!(Action::try_from("X10").is_err());
+    }
+
+    #[test]
+    fn reject_a_turn_that_is_not_a_multiple_of_90_degrees() {
+        assert!(Action::try_from("R45").is_err());
+    }
+
+    #[test]
+    fn reject_an_action_starting_with_a_multi_byte_char() {
+        assert!(Action::try_from("é10").is_err());
+    }
+}
+
+#[cfg(test)]
+mod parts_should {
+    use super::*;
+
+    #[test]
+    fn fail_when_the_ship_sails_beyond_64_bit_coordinates() {
+        let actions = get_actions("F9223372036854775807\nE1").unwrap();
+
+        assert!(Day12.part1(&actions).is_err());
+        assert!(Day12.part2(&actions).is_err());
+    }
+
+    #[test]
+    fn measure_a_distance_beyond_64_bits() {
+        let actions = get_actions("F9223372036854775807\nN9223372036854775807").unwrap();
+
+        assert_eq!(
+            Day12.part1(&actions),
+            Ok(Answer::Integer(2 * isize::MAX as i128))
+        );
+    }
+}
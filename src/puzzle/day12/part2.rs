@@ -1,139 +1,93 @@
 use super::Action;
-use crate::puzzle::day12::Direction;
+use crate::geometry::{Direction, Vector};
 
-pub fn execute(actions: &[Action]) {
-    let ship = Ship::new();
-    let Ship { position, .. } = ship.execute_all(&actions);
-    println!(
-        "12:2 — Manhattan distance from origin after executing all actions: {}",
-        position.manhattan_distance_from_origin(),
-    );
+/// None when the ship or its waypoint goes beyond 64-bit coordinates
+pub fn position_after_all_actions(actions: &[Action]) -> Option<Vector> {
+    let Ship { position, .. } = Ship::new().execute_all(actions)?;
+    Some(position)
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
 struct Ship {
-    position: Position,
-    waypoint: WayPoint,
+    position: Vector,
+    waypoint: Vector,
 }
 
 impl Ship {
     fn new() -> Self {
         Self {
-            position: Position::new(0, 0),
-            waypoint: WayPoint::new(10, 1),
+            position: Vector::ORIGIN,
+            waypoint: Vector::new(10, -1),
         }
     }
 
-    fn execute(&self, action: Action) -> Self {
-        match action {
-            Action::Forward(distance) => Self {
-                position: self.position.move_forward(&self.waypoint, distance),
-                ..*self
-            },
-            Action::East(distance) => Self {
-                waypoint: self.waypoint.translate(Direction::East, distance),
-                ..*self
-            },
-            Action::South(distance) => Self {
-                waypoint: self.waypoint.translate(Direction::South, distance),
-                ..*self
-            },
-            Action::West(distance) => Self {
-                waypoint: self.waypoint.translate(Direction::West, distance),
-                ..*self
-            },
-            Action::North(distance) => Self {
-                waypoint: self.waypoint.translate(Direction::North, distance),
+    fn execute(&self, action: Action) -> Option<Self> {
+        Some(match action {
+            Action::Forward(times) => Self {
+                position: self
+                    .position
+                    .checked_add(self.waypoint.checked_mul(times)?)?,
                 ..*self
             },
+            Action::East(distance) => self.move_waypoint(Direction::East, distance)?,
+            Action::South(distance) => self.move_waypoint(Direction::South, distance)?,
+            Action::West(distance) => self.move_waypoint(Direction::West, distance)?,
+            Action::North(distance) => self.move_waypoint(Direction::North, distance)?,
             Action::Right(angle) => Self {
-                waypoint: self.waypoint.turn(angle),
+                waypoint: self.waypoint.rotate(angle / 90),
                 ..*self
             },
             Action::Left(angle) => Self {
-                waypoint: self.waypoint.turn(-angle),
+                waypoint: self.waypoint.rotate(-angle / 90),
                 ..*self
             },
-        }
+        })
     }
 
-    fn execute_all(&self, actions: &[Action]) -> Ship {
+    fn move_waypoint(&self, direction: Direction, distance: isize) -> Option<Self> {
+        Some(Self {
+            waypoint: self
+                .waypoint
+                .checked_add(direction.vector().checked_mul(distance)?)?,
+            ..*self
+        })
+    }
+
+    fn execute_all(&self, actions: &[Action]) -> Option<Ship> {
         actions
             .iter()
-            .fold(*self, |ship, action| ship.execute(*action))
+            .try_fold(*self, |ship, action| ship.execute(*action))
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-struct Position {
-    x: i32,
-    y: i32,
-}
-
-impl Position {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
-    }
-
-    fn move_forward(&self, waypoint: &WayPoint, times: i32) -> Self {
-        Self {
-            x: self.x + waypoint.x * times,
-            y: self.y + waypoint.y * times,
-        }
-    }
-
-    fn manhattan_distance_from_origin(&self) -> u32 {
-        (self.x.abs() + self.y.abs()) as u32
-    }
-}
+#[cfg(test)]
+mod ship_execute_should {
+    use super::*;
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-struct WayPoint {
-    x: i32,
-    y: i32,
-}
+    #[test]
+    fn rotate_the_waypoint_around_the_ship() {
+        let ship = Ship {
+            position: Vector::new(170, -38),
+            waypoint: Vector::new(10, -4),
+        };
 
-impl WayPoint {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
+        assert_eq!(
+            ship.execute(Action::Right(90)).unwrap().waypoint,
+            Vector::new(4, 10)
+        );
+        assert_eq!(
+            ship.execute(Action::Left(270)).unwrap().waypoint,
+            Vector::new(4, 10)
+        );
     }
 
-    fn turn(&self, angle: i32) -> Self {
-        match (angle / 90) % 4 {
-            0 => *self,
-            1 | -3 => Self {
-                x: self.y,
-                y: -self.x,
-            },
-            2 | -2 => Self {
-                x: -self.x,
-                y: -self.y,
-            },
-            _ => Self {
-                x: -self.y,
-                y: self.x,
-            },
-        }
-    }
+    #[test]
+    fn move_towards_the_waypoint() {
+        let ship = Ship::new();
 
-    fn translate(&self, direction: Direction, distance: i32) -> Self {
-        match direction {
-            Direction::East => Self {
-                x: self.x + distance,
-                ..*self
-            },
-            Direction::South => Self {
-                y: self.y - distance,
-                ..*self
-            },
-            Direction::West => Self {
-                x: self.x - distance,
-                ..*self
-            },
-            Direction::North => Self {
-                y: self.y + distance,
-                ..*self
-            },
-        }
+        assert_eq!(
+            ship.execute(Action::Forward(10)).unwrap().position,
+            Vector::new(100, -10)
+        );
     }
 }
@@ -1,56 +1,207 @@
-use modinverse::modinverse;
-
-const TIMESTAMP: u32 = 1005162;
-const INPUT_PART_1: [u32; 9] = [19, 41, 823, 23, 17, 29, 443, 37, 13];
-const INPUT_PART_2: [(isize, isize); 9] = [
-    (19, 0),
-    (41, 9),
-    (823, 19),
-    (23, 27),
-    (17, 36),
-    (29, 48),
-    (443, 50),
-    (37, 56),
-    (13, 63),
-];
-
-pub fn execute() {
-    let (bus, departure) = compute_earliest_bus(TIMESTAMP, &INPUT_PART_1);
-    println!(
-        "13:1 — Product of earliest timestamp and bus: {}",
-        bus * (departure - TIMESTAMP),
-    );
-    println!(
-        "13:2 — Earliest timestamp with departure aligned by position: {}",
-        compute_earliest_timestamp_with_departure_aligned_by_position(&INPUT_PART_2),
-    );
+use crate::error::ParseError;
+use crate::math::{chinese_remainder, MathError};
+use crate::puzzle::input::parse_number;
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
+use crate::trace::trace;
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Model = Notes;
+
+    fn day(&self) -> u8 {
+        13
+    }
+
+    fn title(&self) -> &'static str {
+        "Shuttle Search"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Product of earliest timestamp and bus",
+            "Earliest timestamp with departure aligned by position",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Notes, ParseError> {
+        parse_notes(input)
+    }
+
+    fn part1(&self, notes: &Notes) -> Result<Answer, ParseError> {
+        let buses = notes
+            .buses
+            .iter()
+            .map(|(b, _)| *b as u64)
+            .collect::<Vec<_>>();
+        let timestamp = u64::from(notes.timestamp);
+        let (bus, departure) = compute_earliest_bus(timestamp, &buses);
+        Ok((bus as i128 * (departure - timestamp) as i128).into())
+    }
+
+    fn part2(&self, notes: &Notes) -> Result<Answer, ParseError> {
+        compute_earliest_timestamp_with_departure_aligned_by_position(&notes.buses)
+            .map(Answer::from)
+            .map_err(|error| ParseError::new("", error.to_string()))
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_notes(random, size))
+    }
 }
 
-fn compute_earliest_bus(timestamp: u32, buses: &[u32]) -> (u32, u32) {
+static EXAMPLES: &[Example] = &[Example {
+    input: r"939
+7,13,x,x,59,x,31,19",
+    part1: Some(295),
+    part2: Some(1068781),
+    solver: None,
+}];
+
+#[derive(PartialEq, Debug)]
+pub struct Notes {
+    timestamp: u32,
+    buses: Vec<(isize, isize)>,
+}
+
+fn parse_notes(input: &str) -> Result<Notes, ParseError> {
+    let mut lines = input.lines();
+    let timestamp = lines
+        .next()
+        .ok_or_else(|| ParseError::new("", "missing timestamp line"))
+        .and_then(parse_number)
+        .map_err(|error| error.at_line(1))?;
+    let buses = lines
+        .next()
+        .ok_or_else(|| ParseError::new("", "missing bus line"))
+        .and_then(parse_buses)
+        .map_err(|error| error.at_line(2))?;
+    Ok(Notes { timestamp, buses })
+}
+
+fn parse_buses(line: &str) -> Result<Vec<(isize, isize)>, ParseError> {
+    let buses = line
+        .split(',')
+        .enumerate()
+        .filter(|(_, bus)| bus.trim() != "x")
+        .map(|(i, bus)| match parse_number(bus)? {
+            id if id > 0 => Ok((id, i as isize)),
+            _ => Err(ParseError::new(bus, "bus id must be positive")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if buses.is_empty() {
+        Err(ParseError::new(line, "no bus in service"))
+    } else {
+        Ok(buses)
+    }
+}
+
+/// There is always at least one bus, whose ids are positive
+fn compute_earliest_bus(timestamp: u64, buses: &[u64]) -> (u64, u64) {
     buses
         .iter()
-        .map(|b| (*b, ((timestamp - 1) / b + 1) * b))
+        .map(|b| (*b, timestamp.div_ceil(*b) * b))
         .min_by_key(|(_, t)| *t)
-        .unwrap()
+        .expect("Notes have at least one bus")
 }
 
-fn chinese_remainder(entries: &[(isize, isize)]) -> isize {
-    let product: isize = entries.iter().map(|(id, _)| id).product();
-    entries
+/// Bus ids that are not pairwise coprime may leave no such timestamp
+fn compute_earliest_timestamp_with_departure_aligned_by_position(
+    buses: &[(isize, isize)],
+) -> Result<i128, MathError> {
+    let congruences = buses
         .iter()
-        .flat_map(|(m, r)| {
-            let p = product / m;
-            modinverse(p, *m).map(|inv| inv * r * p)
-        })
-        .sum::<isize>()
-        % product
+        .map(|(b, o)| (-(*o as i128), *b as i128))
+        .collect::<Vec<_>>();
+    for (r, m) in &congruences {
+        trace!(Info, "t ≡ {} (mod {})", r, m);
+    }
+    let (timestamp, period) = chinese_remainder(&congruences)?;
+    trace!(Info, "repeats every {}", period);
+    Ok(timestamp)
 }
 
-fn compute_earliest_timestamp_with_departure_aligned_by_position(
-    buses: &[(isize, isize)],
-) -> isize {
-    let entries = buses.iter().map(|(b, o)| (*b, b - o)).collect::<Vec<_>>();
-    chinese_remainder(&entries)
+/// `size` buses with distinct prime ids, as many as fit in 100 bits. The timestamp of the second
+/// part is picked first, and each bus takes the first free position that departs after it by as
+/// many minutes.
+fn generate_notes(random: &mut Random, size: usize) -> Generated {
+    let mut primes = (5..1000u128)
+        .filter(|n| (2..*n).take_while(|d| d * d <= *n).all(|d| n % d != 0))
+        .collect::<Vec<_>>();
+    random.shuffle(&mut primes);
+    let (mut ids, mut period) = (Vec::new(), 1);
+    for id in primes {
+        if ids.len() == size.max(1) {
+            break;
+        }
+        if period * id < 1 << 100 {
+            period *= id;
+            ids.push(id);
+        }
+    }
+    let aligned = ((random.next_u64() as u128) << 64 | random.next_u64() as u128) % period;
+    let mut positions = Vec::<u128>::with_capacity(ids.len());
+    for id in &ids {
+        let mut position = (id - aligned % id) % id;
+        while positions.contains(&position) {
+            position += id;
+        }
+        positions.push(position);
+    }
+    let mut buses = vec!["x".to_string(); *positions.iter().max().unwrap() as usize + 1];
+    for (id, position) in ids.iter().zip(&positions) {
+        buses[*position as usize] = id.to_string();
+    }
+    let timestamp = random.between(100_000, 1_000_000) as u128;
+    let (id, departure) = ids
+        .iter()
+        .zip(&positions)
+        .map(|(id, position)| (position, id, timestamp.div_ceil(*id) * id))
+        .min_by_key(|(position, _, departure)| (*departure, **position))
+        .map(|(_, id, departure)| (id, departure))
+        .unwrap();
+    Generated {
+        input: format!("{}\n{}", timestamp, buses.join(",")),
+        part1: Some((id * (departure - timestamp)) as i128),
+        part2: Some(aligned as i128),
+    }
+}
+
+#[cfg(test)]
+mod parse_notes_should {
+    use super::*;
+
+    #[test]
+    fn return_the_timestamp_and_the_buses_with_their_position() {
+        assert_eq!(
+            parse_notes("939\n7,13,x,x,59,x,31,19\n"),
+            Ok(Notes {
+                timestamp: 939,
+                buses: vec![(7, 0), (13, 1), (59, 4), (31, 6), (19, 7)],
+            })
+        );
+    }
+
+    #[test]
+    fn fail_when_the_bus_line_is_missing() {
+        assert_eq!(
+            parse_notes("939\n"),
+            Err(ParseError::new("", "missing bus line").at_line(2))
+        );
+    }
+
+    #[test]
+    fn fail_when_a_bus_id_is_zero() {
+        assert_eq!(
+            parse_notes("939\n7,0\n"),
+            Err(ParseError::new("0", "bus id must be positive").at_line(2))
+        );
+    }
 }
 
 #[cfg(test)]
@@ -65,7 +216,7 @@ mod compute_earliest_timestamp_with_departure_aligned_by_position_should {
                 (13, 2),
                 (19, 3)
             ]),
-            3417,
+            Ok(3417),
         )
     }
 
@@ -78,7 +229,7 @@ mod compute_earliest_timestamp_with_departure_aligned_by_position_should {
                 (59, 2),
                 (61, 3),
             ]),
-            754018,
+            Ok(754018),
         )
     }
 
@@ -91,7 +242,7 @@ mod compute_earliest_timestamp_with_departure_aligned_by_position_should {
                 (59, 3),
                 (61, 4),
             ]),
-            779210,
+            Ok(779210),
         )
     }
 
@@ -104,7 +255,7 @@ mod compute_earliest_timestamp_with_departure_aligned_by_position_should {
                 (59, 3),
                 (61, 4),
             ]),
-            1261476,
+            Ok(1261476),
         )
     }
 
@@ -117,7 +268,50 @@ mod compute_earliest_timestamp_with_departure_aligned_by_position_should {
                 (47, 2),
                 (1889, 3),
             ]),
-            1202161486,
+            Ok(1202161486),
+        )
+    }
+
+    #[test]
+    fn fail_when_the_bus_ids_leave_no_aligned_timestamp() {
+        assert_eq!(
+            compute_earliest_timestamp_with_departure_aligned_by_position(&[(2, 0), (4, 1)]),
+            Err(MathError::NoSolution),
         )
     }
 }
+
+#[cfg(test)]
+mod part1_should {
+    use super::*;
+
+    #[test]
+    fn not_truncate_a_bus_id_above_32_bits() {
+        let notes = Day13.parse("10\n4294967296,x").unwrap();
+
+        assert_eq!(
+            Day13.part1(&notes),
+            Ok(Answer::from(4294967296u64 * 4294967286))
+        );
+    }
+}
+
+#[cfg(test)]
+mod part2_should {
+    use super::*;
+
+    #[test]
+    fn fail_when_no_timestamp_aligns_the_departures() {
+        let notes = Day13
+            .parse(
+                "939
+2,4",
+            )
+            .unwrap();
+
+        assert_eq!(
+            Day13.part2(&notes),
+            Err(ParseError::new("", "Congruences are incompatible"))
+        );
+    }
+}
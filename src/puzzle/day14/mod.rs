@@ -1,28 +1,92 @@
 use std::collections::HashMap;
 
+use crate::error::ParseError;
 use crate::puzzle::day14::InitializationInstruction::Write;
-use crate::puzzle::input::read_lines;
+use crate::puzzle::input::{parse_lines, parse_number};
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::convert::TryFrom;
 
-mod part2;
+pub(crate) mod part2;
 
-pub fn execute() {
-    let instructions = read_lines("day14")
-        .unwrap()
-        .iter()
-        .map(|l| InitializationInstruction::try_from(l.as_str()))
-        .filter(Result::is_ok)
-        .map(Result::unwrap)
-        .collect::<Vec<_>>();
-    let mut program = DockingProgram::new();
-    program.execute_all(&instructions);
-    println!(
-        "14:1 — Sum of memory after initialization: {}",
-        program.memory_sum()
-    );
-    part2::execute();
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Model = InitializationProgram;
+
+    fn day(&self) -> u8 {
+        14
+    }
+
+    fn title(&self) -> &'static str {
+        "Docking Data"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Sum of memory after initialization",
+            "Sum of memory after initialization with memory address decoder",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<InitializationProgram, ParseError> {
+        Ok(InitializationProgram {
+            instructions: parse_lines(input, InitializationInstruction::try_from)?,
+            decoder_instructions: parse_lines(input, part2::InitializationInstruction::try_from),
+        })
+    }
+
+    fn part1(&self, program: &InitializationProgram) -> Result<Answer, ParseError> {
+        let mut docking_program = DockingProgram::new();
+        docking_program.execute_all(&program.instructions);
+        Ok(docking_program.memory_sum().into())
+    }
+
+    fn part2(&self, program: &InitializationProgram) -> Result<Answer, ParseError> {
+        let instructions = program
+            .decoder_instructions
+            .as_ref()
+            .map_err(Clone::clone)?;
+        Ok(part2::memory_sum_after_initialization(instructions).into())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_program(random, size))
+    }
+}
+
+static EXAMPLES: &[Example] = &[
+    Example {
+        input: r"mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
+mem[8] = 11
+mem[7] = 101
+mem[8] = 0",
+        part1: Some(165),
+        part2: None,
+        solver: None,
+    },
+    Example {
+        input: r"mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1",
+        part1: None,
+        part2: Some(208),
+        solver: None,
+    },
+];
+
+/// The instructions read by the decoder of each part. The second decoder has its own limits, which
+/// only fail its part.
+pub struct InitializationProgram {
+    instructions: Vec<InitializationInstruction>,
+    decoder_instructions: Result<Vec<part2::InitializationInstruction>, ParseError>,
 }
 
 struct DockingProgram {
@@ -64,19 +128,19 @@ impl DockingProgram {
     }
 }
 
-enum InitializationInstruction {
+pub(crate) enum InitializationInstruction {
     Masks { and: usize, or: usize },
     Write { value: usize, destination: usize },
 }
 
 lazy_static! {
-    static ref MASKS_REGEX: Regex = Regex::new(r"mask = (?P<mask>[X01]{36})").unwrap();
+    static ref MASKS_REGEX: Regex = Regex::new(r"^mask = (?P<mask>[X01]{36})$").unwrap();
     static ref WRITE_REGEX: Regex =
-        Regex::new(r"mem\[(?P<destination>\d+)\] = (?P<value>\d+)").unwrap();
+        Regex::new(r"^mem\[(?P<destination>\d+)\] = (?P<value>\d+)$").unwrap();
 }
 
 impl TryFrom<&str> for InitializationInstruction {
-    type Error = ();
+    type Error = ParseError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
         if let Some(captures) = MASKS_REGEX.captures(s) {
@@ -97,20 +161,75 @@ impl TryFrom<&str> for InitializationInstruction {
                 .sum();
             Ok(InitializationInstruction::Masks { and, or })
         } else if let Some(captures) = WRITE_REGEX.captures(s) {
-            let value = captures.name("value").unwrap().as_str().parse().unwrap();
-            let destination = captures
-                .name("destination")
-                .unwrap()
-                .as_str()
-                .parse()
-                .unwrap();
+            let value = parse_number(captures.name("value").unwrap().as_str())?;
+            let destination = parse_number(captures.name("destination").unwrap().as_str())?;
             Ok(InitializationInstruction::Write { value, destination })
         } else {
-            Err(())
+            Err(ParseError::new(
+                s,
+                "expected a mask (mask = X01...) or a write (mem[8] = 11)",
+            ))
         }
     }
 }
 
+/// Masks have at most as many floating bits as the personal inputs, since the decoder of the
+/// second part writes to 2 to the power of that many addresses. Both memories are written from the
+/// mask characters while the lines are drawn.
+fn generate_program(random: &mut Random, size: usize) -> Generated {
+    let nb_lines = size.max(2);
+    let mut lines = Vec::with_capacity(nb_lines);
+    let mut memory = HashMap::new();
+    let mut decoded_memory = HashMap::new();
+    let mut bits = (0..36).collect::<Vec<_>>();
+    while lines.len() < nb_lines {
+        let mut mask = (0..36)
+            .map(|_| if random.chance(1, 2) { '1' } else { '0' })
+            .collect::<Vec<_>>();
+        random.shuffle(&mut bits);
+        let nb_floating_bits = random.between(1, part2::MAX_FLOATING_BITS as u64) as usize;
+        for i in &bits[..nb_floating_bits] {
+            mask[*i] = 'X';
+        }
+        lines.push(format!("mask = {}", mask.iter().collect::<String>()));
+        let bit = |i: usize| 1u64 << (35 - i);
+        let bits_of = |c: char| {
+            mask.iter()
+                .enumerate()
+                .filter(move |(_, m)| **m == c)
+                .map(|(i, _)| bit(i))
+        };
+        let (zeros, ones) = (bits_of('0').sum::<u64>(), bits_of('1').sum::<u64>());
+        let floating = bits_of('X').collect::<Vec<_>>();
+        for _ in 0..random.between(1, 5) {
+            if lines.len() == nb_lines {
+                break;
+            }
+            let (address, value) = (random.below(1 << 16), random.below(1 << 30));
+            lines.push(format!("mem[{}] = {}", address, value));
+            memory.insert(address, value & !zeros | ones);
+            for combination in 0..1u64 << floating.len() {
+                let decoded = floating.iter().enumerate().fold(
+                    address | ones,
+                    |address, (j, floating_bit)| {
+                        if combination >> j & 1 == 1 {
+                            address | floating_bit
+                        } else {
+                            address & !floating_bit
+                        }
+                    },
+                );
+                decoded_memory.insert(decoded, value);
+            }
+        }
+    }
+    Generated {
+        input: lines.join("\n"),
+        part1: Some(memory.values().map(|value| *value as i128).sum()),
+        part2: Some(decoded_memory.values().map(|value| *value as i128).sum()),
+    }
+}
+
 #[cfg(test)]
 mod docking_program_execute_all {
     use super::*;
@@ -124,9 +243,7 @@ mem[7] = 101
 mem[8] = 0
 "
         .lines()
-        .map(InitializationInstruction::try_from)
-        .filter(Result::is_ok)
-        .map(Result::unwrap)
+        .flat_map(InitializationInstruction::try_from)
         .collect::<Vec<_>>();
 
         let mut program = DockingProgram::new();
@@ -135,3 +252,54 @@ mem[8] = 0
         assert_eq!(program.memory_sum(), 165);
     }
 }
+
+#[cfg(test)]
+mod initialization_instruction_should {
+    use super::*;
+
+    #[test]
+    fn reject_an_unknown_instruction() {
+        assert!(InitializationInstruction::try_from("mem[8] += 11").is_err());
+    }
+
+    #[test]
+    fn reject_a_value_that_does_not_fit_in_memory() {
+        assert!(InitializationInstruction::try_from("mem[8] = 99999999999999999999999").is_err());
+    }
+}
+
+#[cfg(test)]
+mod parse_should {
+    use super::*;
+
+    #[test]
+    fn reject_the_program_on_an_invalid_line() {
+        let error = Day14
+            .parse("mask = 000000000000000000000000000000X1001X\nmem[8] += 11")
+            .err();
+
+        assert_eq!(error.and_then(|error| error.line), Some(2));
+    }
+
+    #[test]
+    fn fail_the_second_part_on_a_mask_with_too_many_floating_bits() {
+        let mask = format!("mask = {}", "X".repeat(36));
+        let program = Day14.parse(&format!("{}\nmem[8] = 11", mask)).unwrap();
+
+        assert_eq!(Day14.part1(&program), Ok(Answer::from(11)));
+        assert_eq!(
+            Day14.part2(&program),
+            Err(ParseError::new(&mask, "masks have at most 9 floating bits").at_line(1))
+        );
+    }
+
+    #[test]
+    fn fail_the_second_part_on_a_value_above_36_bits() {
+        let program = Day14.parse("mem[8] = 68719476736").unwrap();
+
+        assert_eq!(
+            Day14.part2(&program).err(),
+            Some(ParseError::new("mem[8] = 68719476736", "values have at most 36 bits").at_line(1))
+        );
+    }
+}
@@ -1,24 +1,19 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
 
-use crate::puzzle::input::read_lines;
 use lazy_static::lazy_static;
 use regex::Regex;
 
-pub fn execute() {
-    let instructions = read_lines("day14")
-        .unwrap()
-        .iter()
-        .map(|l| InitializationInstruction::try_from(l.as_str()))
-        .filter(Result::is_ok)
-        .map(Result::unwrap)
-        .collect::<Vec<_>>();
+use crate::error::ParseError;
+use crate::puzzle::input::parse_number;
+
+/// As many as in the personal inputs; each floating bit doubles the addresses of a write
+pub(crate) const MAX_FLOATING_BITS: usize = 9;
+
+pub fn memory_sum_after_initialization(instructions: &[InitializationInstruction]) -> usize {
     let mut program = DockingProgram::new();
-    program.execute_all(&instructions);
-    println!(
-        "14:2 — Sum of memory after initialization: {}",
-        program.memory_sum()
-    );
+    program.execute_all(instructions);
+    program.memory_sum()
 }
 
 struct DockingProgram {
@@ -77,7 +72,7 @@ fn apply_floating_masks(address: usize, masks: &[usize]) -> Vec<usize> {
     }
 }
 
-enum InitializationInstruction {
+pub(crate) enum InitializationInstruction {
     Masks {
         mask: usize,
         floating_masks: Vec<usize>,
@@ -89,13 +84,13 @@ enum InitializationInstruction {
 }
 
 lazy_static! {
-    static ref MASKS_REGEX: Regex = Regex::new(r"mask = (?P<mask>[X01]{36})").unwrap();
+    static ref MASKS_REGEX: Regex = Regex::new(r"^mask = (?P<mask>[X01]{36})$").unwrap();
     static ref WRITE_REGEX: Regex =
-        Regex::new(r"mem\[(?P<destination>\d+)\] = (?P<value>\d+)").unwrap();
+        Regex::new(r"^mem\[(?P<destination>\d+)\] = (?P<value>\d+)$").unwrap();
 }
 
 impl TryFrom<&str> for InitializationInstruction {
-    type Error = ();
+    type Error = ParseError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
         if let Some(captures) = MASKS_REGEX.captures(s) {
@@ -110,21 +105,29 @@ impl TryFrom<&str> for InitializationInstruction {
                     _ => (),
                 }
             }
+            if floating_masks.len() > MAX_FLOATING_BITS {
+                return Err(ParseError::new(
+                    s,
+                    format!("masks have at most {} floating bits", MAX_FLOATING_BITS),
+                ));
+            }
             Ok(InitializationInstruction::Masks {
                 mask,
                 floating_masks,
             })
         } else if let Some(captures) = WRITE_REGEX.captures(s) {
-            let value = captures.name("value").unwrap().as_str().parse().unwrap();
-            let destination = captures
-                .name("destination")
-                .unwrap()
-                .as_str()
-                .parse()
-                .unwrap();
+            let value: usize = parse_number(captures.name("value").unwrap().as_str())?;
+            let destination = parse_number(captures.name("destination").unwrap().as_str())?;
+            // Written as is by this decoder, so it must fit in the memory
+            if value >> 36 != 0 {
+                return Err(ParseError::new(s, "values have at most 36 bits"));
+            }
             Ok(InitializationInstruction::Write { value, destination })
         } else {
-            Err(())
+            Err(ParseError::new(
+                s,
+                "expected a mask (mask = X01...) or a write (mem[8] = 11)",
+            ))
         }
     }
 }
@@ -142,9 +145,7 @@ mask = 00000000000000000000000000000000X0XX
 mem[26] = 1
 "
         .lines()
-        .map(InitializationInstruction::try_from)
-        .filter(Result::is_ok)
-        .map(Result::unwrap)
+        .flat_map(InitializationInstruction::try_from)
         .collect::<Vec<_>>();
 
         let mut program = DockingProgram::new();
@@ -1,14 +1,74 @@
 use std::collections::HashMap;
 
-pub fn execute() {
-    let input = vec![2, 0, 1, 9, 5, 19];
-    let mut game = MemoryGame::from(&input);
-    println!("15:1 — 2020th round: {}", game.play_until_round(2020));
-    let mut game = MemoryGame::from(&input);
-    println!(
-        "15:1 — 30000000th round: {}",
-        game.play_until_round(30000000)
-    );
+use crate::error::ParseError;
+use crate::puzzle::input::parse_list;
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
+use crate::repl::{parse_argument, ReplError, Session};
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Model = Vec<usize>;
+
+    fn day(&self) -> u8 {
+        15
+    }
+
+    fn title(&self) -> &'static str {
+        "Rambunctious Recitation"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        ["2020th round", "30000000th round"]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<usize>, ParseError> {
+        parse_numbers(input)
+    }
+
+    fn part1(&self, numbers: &Vec<usize>) -> Result<Answer, ParseError> {
+        Ok(spoken_at(numbers, 2020).into())
+    }
+
+    fn part2(&self, numbers: &Vec<usize>) -> Result<Answer, ParseError> {
+        Ok(spoken_at(numbers, 30000000).into())
+    }
+
+    fn session(&self, numbers: Vec<usize>) -> Option<Box<dyn Session>> {
+        Some(Box::new(Recitation::new(numbers)))
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_starting_numbers(random, size))
+    }
+}
+
+static EXAMPLES: &[Example] = &[Example {
+    input: r"0,3,6",
+    part1: Some(436),
+    part2: Some(175594),
+    solver: None,
+}];
+
+fn parse_numbers(input: &str) -> Result<Vec<usize>, ParseError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(ParseError::new(input, "no starting number"));
+    }
+    parse_list(input)
+}
+
+/// A round among the starting numbers is not played
+fn spoken_at(numbers: &Vec<usize>, round: usize) -> usize {
+    match numbers.get(round - 1) {
+        Some(number) => *number,
+        None => MemoryGame::from(numbers).play_until_round(round),
+    }
 }
 
 struct MemoryGame {
@@ -26,6 +86,48 @@ impl MemoryGame {
     }
 }
 
+/// Keeps playing the same game while the requested rounds increase
+struct Recitation {
+    numbers: Vec<usize>,
+    game: MemoryGame,
+}
+
+impl Recitation {
+    fn new(numbers: Vec<usize>) -> Self {
+        let game = MemoryGame::from(&numbers);
+        Self { numbers, game }
+    }
+}
+
+impl Session for Recitation {
+    fn namespace(&self) -> &'static str {
+        "game"
+    }
+
+    fn commands(&self) -> &'static [&'static str] {
+        &["round <n> — number spoken at a round"]
+    }
+
+    fn execute(&mut self, command: &str, arguments: &[String]) -> Result<String, ReplError> {
+        match command {
+            "round" => {
+                let round: usize = parse_argument(arguments, 0, "round")?;
+                if round == 0 {
+                    return Err(ReplError::InvalidArgument(arguments[0].clone()));
+                }
+                if round <= self.numbers.len() {
+                    return Ok(self.numbers[round - 1].to_string());
+                }
+                if round < self.game.round {
+                    self.game = MemoryGame::from(&self.numbers);
+                }
+                Ok(self.game.play_until_round(round).to_string())
+            }
+            _ => Err(ReplError::UnknownCommand(command.into())),
+        }
+    }
+}
+
 impl From<&Vec<usize>> for MemoryGame {
     fn from(numbers: &Vec<usize>) -> Self {
         let previous_numbers = numbers
@@ -59,6 +161,32 @@ impl Iterator for MemoryGame {
     }
 }
 
+/// `size` distinct starting numbers; the number of rounds is set by the puzzle. The first part is
+/// checked by searching the whole recitation for each number, too slow for the second.
+fn generate_starting_numbers(random: &mut Random, size: usize) -> Generated {
+    let size = size.max(1);
+    let mut numbers = (0..2 * size).collect::<Vec<_>>();
+    random.shuffle(&mut numbers);
+    let mut spoken = numbers[..size].to_vec();
+    while spoken.len() < 2020 {
+        let (last, before) = spoken.split_last().expect("At least one starting number");
+        let age = before
+            .iter()
+            .rposition(|number| number == last)
+            .map_or(0, |round| before.len() - round);
+        spoken.push(age);
+    }
+    Generated {
+        input: numbers[..size]
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+        part1: Some(spoken[2019] as i128),
+        part2: None,
+    }
+}
+
 #[cfg(test)]
 mod memory_game_play_until_round_should {
     use super::*;
@@ -123,3 +251,61 @@ mod memory_game_play_until_round_should {
         assert_eq!(game.play_until_round(2020), 1836);
     }
 }
+
+#[cfg(test)]
+mod spoken_at_should {
+    use super::*;
+
+    #[test]
+    fn return_the_starting_number_of_the_round_when_there_are_enough() {
+        let numbers = (0..2100).rev().collect::<Vec<_>>();
+
+        assert_eq!(spoken_at(&numbers, 2020), 80);
+    }
+}
+
+#[cfg(test)]
+mod parse_numbers_should {
+    use super::*;
+
+    #[test]
+    fn return_the_starting_numbers() {
+        assert_eq!(parse_numbers("0,3,6\n"), Ok(vec![0, 3, 6]));
+    }
+
+    #[test]
+    fn fail_without_starting_number() {
+        assert!(parse_numbers("\n").is_err());
+    }
+}
+
+#[cfg(test)]
+mod recitation_session_should {
+    use super::*;
+    use crate::repl::run_command;
+
+    #[test]
+    fn return_the_number_spoken_at_a_round() {
+        let mut recitation = Recitation::new(vec![0, 3, 6]);
+
+        assert_eq!(
+            run_command(&mut recitation, "game round 2020"),
+            Ok("436".into())
+        );
+    }
+
+    #[test]
+    fn replay_the_game_for_an_earlier_round() {
+        let mut recitation = Recitation::new(vec![0, 3, 6]);
+        run_command(&mut recitation, "game round 10").unwrap();
+
+        assert_eq!(run_command(&mut recitation, "game round 4"), Ok("0".into()));
+    }
+
+    #[test]
+    fn return_a_starting_number() {
+        let mut recitation = Recitation::new(vec![0, 3, 6]);
+
+        assert_eq!(run_command(&mut recitation, "game round 2"), Ok("3".into()));
+    }
+}
@@ -1,71 +1,143 @@
-use crate::puzzle::input::read_lines;
+use crate::error::ParseError;
+use crate::puzzle::input::{groups, parse_list, parse_number, Group, Groups};
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
+use crate::trace::trace;
 use lazy_static::lazy_static;
 use regex::{Captures, Regex};
 use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
 use std::ops::RangeInclusive;
 
-static TICKET: [u32; 20] = [
-    103, 79, 61, 97, 109, 67, 89, 83, 59, 53, 139, 131, 101, 113, 149, 127, 71, 73, 107, 137,
-];
+pub struct Day16;
 
-pub fn execute() {
-    let rules = read_lines("day16_rules")
-        .unwrap()
-        .iter()
-        .map(|rule| ColumnRule::from(rule.as_str()))
-        .collect::<Vec<_>>();
-    let nearby_tickets = read_lines("day16_nearby_tickets")
-        .unwrap()
-        .iter()
-        .map(|ticket| get_ticket(ticket))
-        .collect::<Vec<_>>();
+impl Solution for Day16 {
+    type Model = Notes;
 
-    let sum_of_invalid_columns: u32 = nearby_tickets
-        .iter()
-        .flat_map(|ticket| get_invalid_values(ticket, &rules))
-        .sum();
-    println!(
-        "16:1 — Sum of all invalid columns: {}",
-        sum_of_invalid_columns
-    );
-
-    let valid_tickets = nearby_tickets
-        .into_iter()
-        .filter(|ticket| get_invalid_values(ticket, &rules).is_empty())
-        .collect::<Vec<_>>();
-    let matches = match_columns_to_names(&valid_tickets, &rules);
-    let column_names = allocate_columns(matches);
-    let departure_indexes: Vec<_> = column_names
-        .into_iter()
-        .filter(|(_, name)| name.starts_with("departure"))
-        .map(|(i, _)| i)
-        .collect();
-    let product_of_departure_columns = departure_indexes
-        .into_iter()
-        .map(|i| TICKET[i] as u64)
-        .product::<u64>();
-
-    println!(
-        "16:1 — Product  of departure columns: {}",
-        product_of_departure_columns,
-    );
+    fn day(&self) -> u8 {
+        16
+    }
+
+    fn title(&self) -> &'static str {
+        "Ticket Translation"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        ["Sum of all invalid columns", "Product of departure columns"]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Notes, ParseError> {
+        parse_notes(input)
+    }
+
+    fn part1(&self, notes: &Notes) -> Result<Answer, ParseError> {
+        Ok(notes
+            .nearby_tickets
+            .iter()
+            .flat_map(|ticket| get_invalid_values(ticket, &notes.rules))
+            .map(u64::from)
+            .sum::<u64>()
+            .into())
+    }
+
+    fn part2(&self, notes: &Notes) -> Result<Answer, ParseError> {
+        let valid_tickets = notes
+            .nearby_tickets
+            .iter()
+            .filter(|ticket| get_invalid_values(ticket, &notes.rules).is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+        let matches = match_columns_to_names(&valid_tickets, &notes.rules);
+        let column_names = allocate_columns(matches)
+            .ok_or_else(|| ParseError::new("", "the columns do not match the rules one to one"))?;
+        column_names
+            .into_iter()
+            .filter(|(_, name)| name.starts_with("departure"))
+            .try_fold(1u64, |product, (i, _)| {
+                product.checked_mul(notes.ticket[i] as u64)
+            })
+            .map(Answer::from)
+            .ok_or_else(|| ParseError::new("", "the product of the departure values overflows"))
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_notes(random, size))
+    }
+}
+
+static EXAMPLES: &[Example] = &[Example {
+    input: r"class: 1-3 or 5-7
+row: 6-11 or 33-44
+seat: 13-40 or 45-50
+
+your ticket:
+7,1,14
+
+nearby tickets:
+7,3,47
+40,4,50
+55,2,20
+38,6,12",
+    part1: Some(71),
+    part2: None,
+    solver: None,
+}];
+
+pub struct Notes {
+    rules: Vec<ColumnRule>,
+    ticket: Vec<u32>,
+    nearby_tickets: Vec<Vec<u32>>,
+}
+
+fn parse_notes(input: &str) -> Result<Notes, ParseError> {
+    let mut groups = groups(input);
+    let rules = match groups.next() {
+        Some(group) => group.parse_lines(|line| ColumnRule::try_from(line.trim()))?,
+        None => Vec::new(),
+    };
+    let parse_ticket = |line: &str| {
+        parse_list(line).and_then(|ticket: Vec<u32>| {
+            if ticket.len() == rules.len() {
+                Ok(ticket)
+            } else {
+                Err(ParseError::new(line, "expected one value per rule"))
+            }
+        })
+    };
+    let ticket = find_section(&mut groups, "your ticket:")
+        .map(|section| section.parse_lines(parse_ticket))
+        .transpose()?
+        .and_then(|tickets| tickets.into_iter().next())
+        .ok_or_else(|| ParseError::new("", "missing your ticket section"))?;
+    let nearby_tickets = match find_section(&mut groups, "nearby tickets:") {
+        Some(section) => section.parse_lines(parse_ticket)?,
+        None => Vec::new(),
+    };
+    Ok(Notes {
+        rules,
+        ticket,
+        nearby_tickets,
+    })
+}
+
+fn find_section<'a>(groups: &mut Groups<'a>, header: &str) -> Option<Group<'a>> {
+    groups
+        .find(|group| group.lines[0].trim() == header)
+        .map(|group| group.body())
 }
 
 fn get_invalid_values(values: &[u32], rules: &[ColumnRule]) -> Vec<u32> {
     values
         .iter()
-        .filter(|value| rules.iter().all(|rule| !rule.is_valid(*value)))
+        .filter(|value| rules.iter().all(|rule| !rule.is_valid(value)))
         .copied()
         .collect()
 }
 
-fn get_ticket(ticket: &str) -> Vec<u32> {
-    ticket
-        .split(',')
-        .map(|value| value.parse().unwrap())
-        .collect()
-}
-
 fn find_rules(values: &[u32], rules: &[ColumnRule]) -> Vec<String> {
     rules
         .iter()
@@ -78,21 +150,25 @@ fn match_columns_to_names(
     tickets: &[Vec<u32>],
     rules: &[ColumnRule],
 ) -> HashMap<usize, Vec<String>> {
-    (0..tickets[0].len())
+    (0..rules.len())
         .map(|i| {
             (
                 i,
                 tickets.iter().map(|ticket| ticket[i]).collect::<Vec<_>>(),
             )
         })
-        .map(|(i, values)| (i, find_rules(&values, &rules)))
+        .map(|(i, values)| (i, find_rules(&values, rules)))
         .collect::<HashMap<_, _>>()
 }
 
-fn allocate_columns(mut matches: HashMap<usize, Vec<String>>) -> HashMap<usize, String> {
+/// Resolves the columns with a single possible name until every column has one, which fails when a
+/// column has none left or when every remaining column has several
+fn allocate_columns(mut matches: HashMap<usize, Vec<String>>) -> Option<HashMap<usize, String>> {
     let mut result = HashMap::new();
     let mut allocated_columns = HashSet::new();
+    let mut round = 0;
     while !matches.is_empty() {
+        round += 1;
         let updated_matches = matches
             .into_iter()
             .map(|(i, names)| {
@@ -105,23 +181,39 @@ fn allocate_columns(mut matches: HashMap<usize, Vec<String>>) -> HashMap<usize,
                 )
             })
             .collect::<Vec<_>>();
-        updated_matches
-            .iter()
-            .filter(|(_, names)| names.len() == 1)
-            .for_each(|(i, names)| {
-                allocated_columns.insert(names[0].clone());
-                result.insert(*i, names[0].clone());
-            });
+        if updated_matches.iter().all(|(_, names)| names.len() != 1) {
+            return None;
+        }
+        for (i, names) in updated_matches.iter().filter(|(_, names)| names.len() < 2) {
+            let name = names.first()?;
+            trace!(Info, "round {}: column {} is {}", round, i, name);
+            if !allocated_columns.insert(name.clone()) {
+                return None;
+            }
+            result.insert(*i, name.clone());
+        }
         matches = updated_matches
             .into_iter()
             .filter(|(_, names)| names.len() > 1)
             .collect();
+        for (i, names) in &matches {
+            trace!(
+                Debug,
+                "round {}: column {} may be {:?}",
+                round,
+                i,
+                names
+                    .iter()
+                    .filter(|name| !allocated_columns.contains(*name))
+                    .collect::<Vec<_>>()
+            );
+        }
     }
-    result
+    Some(result)
 }
 
 #[derive(Debug)]
-struct ColumnRule {
+pub(crate) struct ColumnRule {
     name: String,
     first_range: RangeInclusive<u32>,
     second_range: RangeInclusive<u32>,
@@ -139,19 +231,162 @@ lazy_static! {
             .unwrap();
 }
 
-impl From<&str> for ColumnRule {
-    fn from(rule: &str) -> Self {
-        let captures = COLUMN_RULE_REGEX.captures(rule).unwrap();
-        Self {
+impl TryFrom<&str> for ColumnRule {
+    type Error = ParseError;
+
+    fn try_from(rule: &str) -> Result<Self, Self::Error> {
+        let captures = COLUMN_RULE_REGEX
+            .captures(rule)
+            .ok_or_else(|| ParseError::new(rule, "expected a rule (e.g. class: 1-3 or 5-7)"))?;
+        Ok(Self {
             name: captures.name("column").unwrap().as_str().to_string(),
-            first_range: get_integer(&captures, "s1")..=get_integer(&captures, "e1"),
-            second_range: get_integer(&captures, "s2")..=get_integer(&captures, "e2"),
-        }
+            first_range: get_integer(&captures, "s1")?..=get_integer(&captures, "e1")?,
+            second_range: get_integer(&captures, "s2")?..=get_integer(&captures, "e2")?,
+        })
+    }
+}
+
+fn get_integer(captures: &Captures, name: &str) -> Result<u32, ParseError> {
+    parse_number(captures.name(name).unwrap().as_str())
+}
+
+const FIELDS: [&str; 20] = [
+    "departure location",
+    "departure station",
+    "departure platform",
+    "departure track",
+    "departure date",
+    "departure time",
+    "arrival location",
+    "arrival station",
+    "arrival platform",
+    "arrival track",
+    "class",
+    "duration",
+    "price",
+    "route",
+    "row",
+    "seat",
+    "train",
+    "type",
+    "wagon",
+    "zone",
+];
+
+const STEP: u32 = 40;
+
+/// The rules form a staircase: the k-th rule accepts the values from 1 to 40 (k + 1), and the
+/// values of its column are above 40 k, so the column of the last rule only fits that rule, the
+/// one before it only fits the last two, and so on, leaving a single assignment. The second
+/// ranges are above all the columns, and a quarter of the nearby tickets have a value above all
+/// the ranges.
+fn generate_notes(random: &mut Random, size: usize) -> Generated {
+    let mut names = FIELDS.to_vec();
+    random.shuffle(&mut names);
+    let mut steps = (0..FIELDS.len() as u32).collect::<Vec<_>>();
+    random.shuffle(&mut steps);
+    let mut rules = (0..FIELDS.len() as u32)
+        .map(|step| {
+            let second_range = 900 + 4 * step as u64;
+            format!(
+                "{}: {}-{} or {}-{}",
+                names[step as usize],
+                random.between(1, 5),
+                STEP * (step + 1) - 1,
+                second_range,
+                second_range + random.below(4)
+            )
+        })
+        .collect::<Vec<_>>();
+    random.shuffle(&mut rules);
+    let generate_ticket = |random: &mut Random| {
+        steps
+            .iter()
+            .map(|step| random.between((STEP * step + 5) as u64, (STEP * (step + 1) - 1) as u64))
+            .collect::<Vec<_>>()
+    };
+    let ticket = generate_ticket(random);
+    let mut invalid_values = 0;
+    let nearby_tickets = (0..size.max(1))
+        .map(|i| {
+            let mut values = generate_ticket(random);
+            if i > 0 && random.chance(1, 4) {
+                let invalid_value = random.between(980, 999);
+                values[random.index(FIELDS.len())] = invalid_value;
+                invalid_values += invalid_value;
+            }
+            join_values(&values)
+        })
+        .collect::<Vec<_>>();
+    let departure_values = steps
+        .iter()
+        .zip(&ticket)
+        .filter(|(step, _)| names[**step as usize].starts_with("departure"))
+        .map(|(_, value)| *value as i128)
+        .product();
+    Generated {
+        input: format!(
+            "{}\n\nyour ticket:\n{}\n\nnearby tickets:\n{}",
+            rules.join("\n"),
+            join_values(&ticket),
+            nearby_tickets.join("\n")
+        ),
+        part1: Some(invalid_values as i128),
+        part2: Some(departure_values),
     }
 }
 
-fn get_integer(captures: &Captures, name: &str) -> u32 {
-    captures.name(name).unwrap().as_str().parse().unwrap()
+fn join_values(values: &[u64]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod parse_notes_should {
+    use super::*;
+
+    #[test]
+    fn parse_the_example() {
+        let notes = parse_notes(
+            r"class: 1-3 or 5-7
+row: 6-11 or 33-44
+seat: 13-40 or 45-50
+
+your ticket:
+7,1,14
+
+nearby tickets:
+7,3,47
+40,4,50
+55,2,20
+38,6,12
+",
+        )
+        .unwrap();
+
+        assert_eq!(notes.rules.len(), 3);
+        assert_eq!(notes.ticket, vec![7, 1, 14]);
+        assert_eq!(notes.nearby_tickets.len(), 4);
+        assert_eq!(notes.nearby_tickets[3], vec![38, 6, 12]);
+    }
+
+    #[test]
+    fn locate_a_ticket_without_one_value_per_rule() {
+        let error = parse_notes("class: 1-3 or 5-7\n\nyour ticket:\n7,1\n").err();
+
+        assert_eq!(
+            error,
+            Some(ParseError::new("7,1", "expected one value per rule").at_line(4))
+        );
+    }
+
+    #[test]
+    fn fail_without_your_ticket() {
+        assert!(parse_notes("class: 1-3 or 5-7\n").is_err());
+    }
 }
 
 #[cfg(test)]
@@ -160,19 +395,19 @@ mod column_rule_is_valid_should {
 
     #[test]
     fn return_true_when_value_is_3_and_rule_is_1_to_3_and_5_to_7() {
-        let rule = ColumnRule::from("class: 1-3 or 5-7");
+        let rule = ColumnRule::try_from("class: 1-3 or 5-7").unwrap();
         assert!(rule.is_valid(&3))
     }
 
     #[test]
     fn return_false_when_value_is_4_and_rule_is_1_to_3_and_5_to_7() {
-        let rule = ColumnRule::from("class: 1-3 or 5-7");
+        let rule = ColumnRule::try_from("class: 1-3 or 5-7").unwrap();
         assert!(!rule.is_valid(&4))
     }
 
     #[test]
     fn return_true_when_value_is_5_and_rule_is_1_to_3_and_5_to_7() {
-        let rule = ColumnRule::from("class: 1-3 or 5-7");
+        let rule = ColumnRule::try_from("class: 1-3 or 5-7").unwrap();
         assert!(rule.is_valid(&5))
     }
 }
@@ -190,7 +425,7 @@ mod get_invalid_values_should {
         ]
         .iter()
         .copied()
-        .map(ColumnRule::from)
+        .flat_map(ColumnRule::try_from)
         .collect::<Vec<_>>();
 
         assert!(get_invalid_values(&[7, 3, 47], &rules).is_empty());
@@ -205,7 +440,7 @@ mod get_invalid_values_should {
         ]
         .iter()
         .copied()
-        .map(ColumnRule::from)
+        .flat_map(ColumnRule::try_from)
         .collect::<Vec<_>>();
 
         assert_eq!(get_invalid_values(&[40, 4, 50], &rules), vec![4]);
@@ -220,7 +455,7 @@ mod get_invalid_values_should {
         ]
         .iter()
         .copied()
-        .map(ColumnRule::from)
+        .flat_map(ColumnRule::try_from)
         .collect::<Vec<_>>();
 
         assert_eq!(get_invalid_values(&[40, 4, 51], &rules), vec![4, 51]);
@@ -241,7 +476,7 @@ mod find_rule_should {
         ]
         .iter()
         .copied()
-        .map(ColumnRule::from)
+        .flat_map(ColumnRule::try_from)
         .collect::<Vec<_>>();
 
         assert_eq!(find_rules(&values, &rules), vec![String::from("row")]);
@@ -257,7 +492,7 @@ mod find_rule_should {
         ]
         .iter()
         .copied()
-        .map(ColumnRule::from)
+        .flat_map(ColumnRule::try_from)
         .collect::<Vec<_>>();
 
         assert_eq!(
@@ -276,7 +511,7 @@ mod find_rule_should {
         ]
         .iter()
         .copied()
-        .map(ColumnRule::from)
+        .flat_map(ColumnRule::try_from)
         .collect::<Vec<_>>();
 
         assert_eq!(
@@ -305,7 +540,7 @@ mod match_columns_to_names_test {
         ]
         .iter()
         .copied()
-        .map(ColumnRule::from)
+        .flat_map(ColumnRule::try_from)
         .collect::<Vec<_>>();
 
         let mut expected = HashMap::new();
@@ -337,7 +572,7 @@ mod allocate_columns_test {
         ]
         .iter()
         .copied()
-        .map(ColumnRule::from)
+        .flat_map(ColumnRule::try_from)
         .collect::<Vec<_>>();
 
         let mut expected = HashMap::new();
@@ -346,7 +581,80 @@ mod allocate_columns_test {
         expected.insert(2, String::from("seat"));
         assert_eq!(
             allocate_columns(match_columns_to_names(&tickets, &rules)),
-            expected
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn fail_when_no_column_has_a_single_name() {
+        let mut matches = HashMap::new();
+        matches.insert(0, vec![String::from("class"), String::from("row")]);
+        matches.insert(1, vec![String::from("class"), String::from("row")]);
+
+        assert_eq!(allocate_columns(matches), None);
+    }
+
+    #[test]
+    fn fail_when_two_columns_have_the_same_name() {
+        let mut matches = HashMap::new();
+        matches.insert(0, vec![String::from("row")]);
+        matches.insert(1, vec![String::from("row")]);
+
+        assert_eq!(allocate_columns(matches), None);
+    }
+
+    #[test]
+    fn fail_when_a_column_has_no_name() {
+        let mut matches = HashMap::new();
+        matches.insert(0, vec![String::from("row")]);
+        matches.insert(1, vec![]);
+
+        assert_eq!(allocate_columns(matches), None);
+    }
+}
+
+#[cfg(test)]
+mod part2_should {
+    use super::*;
+
+    #[test]
+    fn fail_without_valid_nearby_tickets() {
+        let notes = Day16
+            .parse("class: 1-3 or 5-7\nrow: 6-11 or 33-44\n\nyour ticket:\n7,1\n\nnearby tickets:\n50,50")
+            .unwrap();
+
+        assert_eq!(
+            Day16.part2(&notes),
+            Err(ParseError::new(
+                "",
+                "the columns do not match the rules one to one"
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod generate_notes_should {
+    use super::*;
+
+    #[test]
+    fn leave_a_single_assignment_of_the_columns() {
+        let notes = parse_notes(&generate_notes(&mut Random::new(16), 50).input).unwrap();
+        let valid_tickets = notes
+            .nearby_tickets
+            .iter()
+            .filter(|ticket| get_invalid_values(ticket, &notes.rules).is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let matches = match_columns_to_names(&valid_tickets, &notes.rules);
+        let mut candidates = matches.values().map(Vec::len).collect::<Vec<_>>();
+        candidates.sort_unstable();
+
+        assert_eq!(candidates, (1..=20).collect::<Vec<_>>());
+        assert_eq!(
+            allocate_columns(matches).map(|columns| columns.len()),
+            Some(20)
         );
     }
 }
@@ -1,30 +1,100 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ops::RangeInclusive;
 
-use crate::puzzle::input::read_input;
+use crate::error::ParseError;
+use crate::iterate::fixpoint;
+use crate::puzzle::input::parse_lines;
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
 use itertools::Itertools;
 
-pub fn execute() {
-    let input = read_input("day17");
-    let mut cubes = ConwayCubes::from(&input, 3);
-    for _ in 0..6 {
-        cubes = cubes.next_cycle()
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Model = Slice;
+
+    fn day(&self) -> u8 {
+        17
     }
-    println!(
-        "17:1 — Number of cubes after 6 cycles: {}",
-        cubes.count_cubes()
-    );
-    let mut cubes = ConwayCubes::from(&input, 4);
-    for _ in 0..6 {
-        cubes = cubes.next_cycle()
+
+    fn title(&self) -> &'static str {
+        "Conway Cubes"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Number of cubes after 6 cycles",
+            "Number of hypercubes after 6 cycles",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Slice, ParseError> {
+        let rows = parse_lines(input, |line| {
+            line.chars()
+                .map(|c| match c {
+                    '#' => Ok(true),
+                    '.' => Ok(false),
+                    _ => Err(ParseError::new(line, "expected only '.' and '#'")),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        Ok(rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, active)| **active)
+                    .map(move |(x, _)| (x as i32, y as i32))
+            })
+            .collect())
+    }
+
+    fn part1(&self, slice: &Slice) -> Result<Answer, ParseError> {
+        Ok(count_cubes_after_6_cycles(slice, 3).into())
+    }
+
+    fn part2(&self, slice: &Slice) -> Result<Answer, ParseError> {
+        Ok(count_cubes_after_6_cycles(slice, 4).into())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_slice(random, size))
     }
-    println!(
-        "17:2 — Number of hypercubes after 6 cycles: {}",
-        cubes.count_cubes()
-    );
 }
 
-struct ConwayCubes {
+static EXAMPLE: &str = "
+.#.
+..#
+###
+";
+
+static EXAMPLES: &[Example] = &[Example {
+    input: EXAMPLE,
+    part1: Some(112),
+    part2: Some(848),
+    solver: None,
+}];
+
+/// The active cubes of the first slice, by (x, y)
+type Slice = HashSet<(i32, i32)>;
+
+fn count_cubes_after_6_cycles(slice: &Slice, nb_dimensions: usize) -> usize {
+    fixpoint(
+        ConwayCubes::from_slice(slice, nb_dimensions),
+        ConwayCubes::next_cycle,
+        Some(6),
+    )
+    .state
+    .count_cubes()
+}
+
+#[derive(PartialEq)]
+pub(crate) struct ConwayCubes {
     cubes: HashSet<Vec<i32>>,
     dimensions: Vec<RangeInclusive<i32>>,
 }
@@ -40,32 +110,24 @@ impl ConwayCubes {
         Self { cubes, dimensions }
     }
 
-    fn from(first_slice: &str, nb_dimensions: usize) -> Self {
-        let cubes = first_slice
-            .lines()
-            .filter(|line| !line.is_empty())
-            .enumerate()
-            // .map(|(i, line)| (i as i32, to_line(line)))
-            .flat_map(|(y, line)| {
-                line.chars()
-                    .enumerate()
-                    .filter(|&(_, c)| c == '#')
-                    .map(move |(x, _)| {
-                        let mut p = Vec::with_capacity(nb_dimensions);
-                        p.push(x as i32);
-                        p.push(y as i32);
-                        for _ in 2..nb_dimensions {
-                            p.push(0)
-                        }
-                        p
-                    })
+    pub(crate) fn from_slice(slice: &Slice, nb_dimensions: usize) -> Self {
+        let cubes = slice
+            .iter()
+            .map(|&(x, y)| {
+                let mut p = Vec::with_capacity(nb_dimensions);
+                p.push(x);
+                p.push(y);
+                for _ in 2..nb_dimensions {
+                    p.push(0)
+                }
+                p
             })
             .collect();
         Self::new(cubes, nb_dimensions)
     }
 
     fn is_active(&self, position: &[i32]) -> bool {
-        self.cubes.iter().any(|p| p == &position)
+        self.cubes.contains(position)
     }
 
     fn count_adjacent_cubes(&self, position: &[i32]) -> usize {
@@ -73,12 +135,12 @@ impl ConwayCubes {
             .iter()
             .map(|i| i - 1..=i + 1)
             .multi_cartesian_product()
-            .filter(|p| p != &position)
+            .filter(|p| p != position)
             .filter(|p| self.cubes.contains(p))
             .count()
     }
 
-    fn next_cycle(&self) -> Self {
+    pub(crate) fn next_cycle(&self) -> Self {
         let cubes = self
             .dimensions
             .iter()
@@ -124,12 +186,63 @@ impl Expand for RangeInclusive<i32> {
     }
 }
 
-#[cfg(test)]
-static EXAMPLE: &str = "
-.#.
-..#
-###
-";
+/// A square slice of about `size` cubes, a third of them active. Only the first part is checked,
+/// the four dimensions of the second being slow to scan.
+fn generate_slice(random: &mut Random, size: usize) -> Generated {
+    let side = size.isqrt().max(1);
+    let rows = (0..side)
+        .map(|_| {
+            (0..side)
+                .map(|_| if random.chance(1, 3) { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>();
+    let slice = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.char_indices()
+                .filter(|(_, c)| *c == '#')
+                .map(move |(x, _)| (x as i32, y as i32))
+        })
+        .collect();
+    Generated {
+        input: rows.join("\n"),
+        part1: Some(count_cubes_by_neighbours_after_6_cycles(&slice) as i128),
+        part2: None,
+    }
+}
+
+/// Counts the neighbours of the active cubes only, where the parts scan the whole bounding box
+fn count_cubes_by_neighbours_after_6_cycles(slice: &Slice) -> usize {
+    let offsets = (0..27)
+        .map(|n| [n % 3 - 1, n / 3 % 3 - 1, n / 9 - 1])
+        .filter(|offset| *offset != [0; 3])
+        .collect::<Vec<_>>();
+    let mut active = slice
+        .iter()
+        .map(|&(x, y)| [x, y, 0])
+        .collect::<HashSet<_>>();
+    for _ in 0..6 {
+        let mut neighbours = HashMap::new();
+        for cube in &active {
+            for offset in &offsets {
+                let neighbour = [
+                    cube[0] + offset[0],
+                    cube[1] + offset[1],
+                    cube[2] + offset[2],
+                ];
+                *neighbours.entry(neighbour).or_insert(0) += 1;
+            }
+        }
+        active = neighbours
+            .into_iter()
+            .filter(|(cube, n)| *n == 3 || *n == 2 && active.contains(cube))
+            .map(|(cube, _)| cube)
+            .collect();
+    }
+    active.len()
+}
 
 #[cfg(test)]
 mod conway_cubes_from_should {
@@ -137,7 +250,7 @@ mod conway_cubes_from_should {
 
     #[test]
     fn parse_example() {
-        let slice = ConwayCubes::from(EXAMPLE, 3);
+        let slice = ConwayCubes::from_slice(&Day17.parse(EXAMPLE).unwrap(), 3);
         assert_eq!(
             slice.cubes,
             vec![
@@ -160,21 +273,21 @@ mod conway_cubes_count_adjacent_cubes_should {
 
     #[test]
     fn return_1_for_0_0_in_the_example() {
-        let slice = ConwayCubes::from(EXAMPLE, 3);
+        let slice = ConwayCubes::from_slice(&Day17.parse(EXAMPLE).unwrap(), 3);
 
         assert_eq!(slice.count_adjacent_cubes(&[0, 0, 0]), 1);
     }
 
     #[test]
     fn return_1_for_1_0_in_the_example() {
-        let slice = ConwayCubes::from(EXAMPLE, 3);
+        let slice = ConwayCubes::from_slice(&Day17.parse(EXAMPLE).unwrap(), 3);
 
         assert_eq!(slice.count_adjacent_cubes(&[1, 0, 0]), 1);
     }
 
     #[test]
     fn return_5_for_1_1_in_the_example() {
-        let slice = ConwayCubes::from(EXAMPLE, 3);
+        let slice = ConwayCubes::from_slice(&Day17.parse(EXAMPLE).unwrap(), 3);
 
         assert_eq!(slice.count_adjacent_cubes(&[1, 1, 0]), 5);
     }
@@ -186,7 +299,7 @@ mod text {
 
     #[test]
     fn example_3_dimensions() {
-        let mut cubes = ConwayCubes::from(EXAMPLE, 3);
+        let mut cubes = ConwayCubes::from_slice(&Day17.parse(EXAMPLE).unwrap(), 3);
         for _ in 0..6 {
             cubes = cubes.next_cycle();
         }
@@ -195,7 +308,7 @@ mod text {
 
     #[test]
     fn example_4_dimensions() {
-        let mut cubes = ConwayCubes::from(EXAMPLE, 4);
+        let mut cubes = ConwayCubes::from_slice(&Day17.parse(EXAMPLE).unwrap(), 4);
         for _ in 0..6 {
             cubes = cubes.next_cycle();
         }
@@ -1,68 +1,119 @@
+use std::convert::TryFrom;
+
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::puzzle::input::read_lines;
+use crate::error::ParseError;
+use crate::puzzle::input::{parse_lines, parse_number};
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Model = Vec<PasswordEntry>;
+
+    fn day(&self) -> u8 {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "Password Philosophy"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Number of valid passwords for sled policy",
+            "Number of valid passwords for toboggan policy",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<PasswordEntry>, ParseError> {
+        parse_lines(input, PasswordEntry::try_from)
+    }
+
+    fn part1(&self, entries: &Vec<PasswordEntry>) -> Result<Answer, ParseError> {
+        Ok(count_valid_passwords_for_sled_policy(entries).into())
+    }
+
+    fn part2(&self, entries: &Vec<PasswordEntry>) -> Result<Answer, ParseError> {
+        Ok(count_valid_passwords_for_toboggan_policy(entries).into())
+    }
 
-pub fn execute() {
-    let entries = read_lines("day2").unwrap();
-    println!(
-        "2:1 — Number of valid passwords for sled policy: {}",
-        count_valid_passwords_for_sled_policy(&entries)
-    );
-    println!(
-        "2:2 — Number of valid passwords for toboggan policy: {}",
-        count_valid_passwords_for_toboggan_policy(&entries)
-    );
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_password_lines(random, size))
+    }
 }
 
-fn count_valid_passwords_for_sled_policy(entries: &[String]) -> usize {
+static EXAMPLES: &[Example] = &[Example {
+    input: r"1-3 a: abcde
+1-3 b: cdefg
+2-9 c: ccccccccc",
+    part1: Some(2),
+    part2: Some(1),
+    solver: None,
+}];
+
+fn count_valid_passwords_for_sled_policy(entries: &[PasswordEntry]) -> usize {
     entries
         .iter()
-        .map(|entry| parse_password_line_to_sled_policy(entry.as_str()))
-        .filter(|(password, policy)| policy.is_valid(password.as_str()))
+        .filter(|entry| entry.sled_policy().is_valid(&entry.password))
         .count()
 }
 
-fn parse_password_line_to_sled_policy(password_line: &str) -> (String, SledPasswordPolicy) {
-    let (password, letter, start, end) = split_password_line(password_line);
-    (
-        password,
-        SledPasswordPolicy::new(letter, start as u32, end as u32),
-    )
-}
-
-fn count_valid_passwords_for_toboggan_policy(entries: &[String]) -> usize {
+fn count_valid_passwords_for_toboggan_policy(entries: &[PasswordEntry]) -> usize {
     entries
         .iter()
-        .map(|entry| parse_password_line_to_toboggan_policy(entry.as_str()))
-        .filter(|(password, policy)| policy.is_valid(password.as_str()))
+        .filter(|entry| entry.toboggan_policy().is_valid(&entry.password))
         .count()
 }
 
-fn parse_password_line_to_toboggan_policy(password_line: &str) -> (String, TobogganPasswordPolicy) {
-    let (password, letter, first_position, second_position) = split_password_line(password_line);
-    (
-        password,
-        TobogganPasswordPolicy::new(letter, first_position, second_position),
-    )
-}
-
-fn split_password_line(password_line: &str) -> (String, char, usize, usize) {
-    let captures = PASSWORD_LINE_REGEX
-        .captures(password_line)
-        .unwrap_or_else(|| panic!("Invalid password line: {}", password_line));
-    (
-        captures.name("password").unwrap().as_str().into(),
-        captures
-            .name("letter")
-            .unwrap()
-            .as_str()
-            .chars()
-            .next()
-            .unwrap(),
-        captures.name("a").unwrap().as_str().parse().unwrap(),
-        captures.name("b").unwrap().as_str().parse().unwrap(),
-    )
+/// A password with the two numbers and the letter of its policy, which each part reads differently
+#[derive(PartialEq, Debug)]
+pub struct PasswordEntry {
+    letter: char,
+    a: usize,
+    b: usize,
+    password: String,
+}
+
+impl PasswordEntry {
+    fn sled_policy(&self) -> SledPasswordPolicy {
+        SledPasswordPolicy::new(self.letter, self.a as u32, self.b as u32)
+    }
+
+    fn toboggan_policy(&self) -> TobogganPasswordPolicy {
+        TobogganPasswordPolicy::new(self.letter, self.a, self.b)
+    }
+}
+
+impl TryFrom<&str> for PasswordEntry {
+    type Error = ParseError;
+
+    fn try_from(password_line: &str) -> Result<Self, Self::Error> {
+        let captures = PASSWORD_LINE_REGEX.captures(password_line).ok_or_else(|| {
+            ParseError::new(
+                password_line,
+                "expected a policy and a password (e.g. 1-3 a: abcde)",
+            )
+        })?;
+        Ok(Self {
+            letter: captures
+                .name("letter")
+                .unwrap()
+                .as_str()
+                .chars()
+                .next()
+                .unwrap(),
+            a: parse_number(captures.name("a").unwrap().as_str())?,
+            b: parse_number(captures.name("b").unwrap().as_str())?,
+            password: captures.name("password").unwrap().as_str().into(),
+        })
+    }
 }
 
 lazy_static! {
@@ -104,20 +155,72 @@ impl TobogganPasswordPolicy {
     }
 
     fn is_valid(&self, password: &str) -> bool {
-        let chars = chars_at(password, self.first_position - 1, self.second_position - 1);
+        let chars = chars_at(password, self.first_position, self.second_position);
         match chars {
             (a, b) if a == b => false,
-            (a, b) if a == self.letter || b == self.letter => true,
+            (a, b) if a == Some(self.letter) || b == Some(self.letter) => true,
             _ => false,
         }
     }
 }
 
-fn chars_at(str: &str, first_position: usize, second_position: usize) -> (char, char) {
-    (
-        str.chars().nth(first_position).unwrap(),
-        str.chars().nth(second_position).unwrap(),
-    )
+/// Positions start at 1, a position outside of the string has no char
+fn chars_at(
+    str: &str,
+    first_position: usize,
+    second_position: usize,
+) -> (Option<char>, Option<char>) {
+    let char_at = |position: usize| {
+        position
+            .checked_sub(1)
+            .and_then(|index| str.chars().nth(index))
+    };
+    (char_at(first_position), char_at(second_position))
+}
+
+/// The second position may be past the end of the password
+fn generate_password_lines(random: &mut Random, size: usize) -> Generated {
+    let mut lines = Vec::with_capacity(size);
+    let (mut valid_for_sled, mut valid_for_toboggan) = (0, 0);
+    for _ in 0..size {
+        let letter = random_letter(random);
+        let length = random.between(4, 20);
+        let password = (0..length)
+            .map(|_| {
+                if random.chance(1, 3) {
+                    letter
+                } else {
+                    random_letter(random)
+                }
+            })
+            .collect::<Vec<_>>();
+        let a = random.between(1, length) as usize;
+        let b = random.between(a as u64 + 1, length + 2) as usize;
+        let count = password.iter().filter(|c| **c == letter).count();
+        if (a..=b).contains(&count) {
+            valid_for_sled += 1;
+        }
+        let is_letter = |position: usize| password.get(position - 1) == Some(&letter);
+        if is_letter(a) != is_letter(b) {
+            valid_for_toboggan += 1;
+        }
+        lines.push(format!(
+            "{}-{} {}: {}",
+            a,
+            b,
+            letter,
+            password.iter().collect::<String>()
+        ));
+    }
+    Generated {
+        input: lines.join("\n"),
+        part1: Some(valid_for_sled),
+        part2: Some(valid_for_toboggan),
+    }
+}
+
+fn random_letter(random: &mut Random) -> char {
+    (b'a' + random.below(26) as u8) as char
 }
 
 #[cfg(test)]
@@ -168,31 +271,43 @@ mod sled_password_policy_is_valid_should {
 }
 
 #[cfg(test)]
-mod parse_password_line_to_sled_policy {
-    use super::{parse_password_line_to_sled_policy, SledPasswordPolicy};
+mod password_entry_try_from_should {
+    use super::*;
+
+    fn entry(letter: char, a: usize, b: usize, password: &str) -> PasswordEntry {
+        PasswordEntry {
+            letter,
+            a,
+            b,
+            password: password.into(),
+        }
+    }
 
     #[test]
     fn parse_first_example() {
-        let result = parse_password_line_to_sled_policy("1-3 a: abcde");
+        let result = PasswordEntry::try_from("1-3 a: abcde");
 
-        assert_eq!(result, ("abcde".into(), SledPasswordPolicy::new('a', 1, 3)));
+        assert_eq!(result, Ok(entry('a', 1, 3, "abcde")));
     }
 
     #[test]
     fn parse_second_example() {
-        let result = parse_password_line_to_sled_policy("1-3 b: cdefg");
+        let result = PasswordEntry::try_from("1-3 b: cdefg");
 
-        assert_eq!(result, ("cdefg".into(), SledPasswordPolicy::new('b', 1, 3)));
+        assert_eq!(result, Ok(entry('b', 1, 3, "cdefg")));
     }
 
     #[test]
-    fn parse_third_example() {
-        let result = parse_password_line_to_sled_policy("2-9 c: ccccccccc");
+    fn parse_third_example_with_its_sled_policy() {
+        let result = PasswordEntry::try_from("2-9 c: ccccccccc").unwrap();
 
-        assert_eq!(
-            result,
-            ("ccccccccc".into(), SledPasswordPolicy::new('c', 2, 9))
-        );
+        assert_eq!(result, entry('c', 2, 9, "ccccccccc"));
+        assert_eq!(result.sled_policy(), SledPasswordPolicy::new('c', 2, 9));
+    }
+
+    #[test]
+    fn reject_a_line_without_policy() {
+        assert!(PasswordEntry::try_from("abcde").is_err());
     }
 }
 
@@ -217,4 +332,10 @@ mod toboggan_password_policy_is_valid_should {
         let policy = TobogganPasswordPolicy::new('c', 2, 9);
         assert!(!policy.is_valid("ccccccccc"))
     }
+
+    #[test]
+    fn ignore_positions_outside_of_the_password() {
+        let policy = TobogganPasswordPolicy::new('a', 0, 12);
+        assert!(!policy.is_valid("abcde"))
+    }
 }
@@ -1,115 +1,137 @@
-use std::ops::{Add, Rem};
-
-use crate::puzzle::input::read_lines;
+use crate::error::ParseError;
+use crate::geometry::Vector;
+use crate::grid::{Cell, Grid};
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
 
 const TREE_CHAR: char = '#';
+const OPEN_CHAR: char = '.';
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Model = Topology;
+
+    fn day(&self) -> u8 {
+        3
+    }
+
+    fn title(&self) -> &'static str {
+        "Toboggan Trajectory"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        ["Number of trees on slope", "Product of trees on all slopes"]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Topology, ParseError> {
+        get_topology(input)
+    }
+
+    fn part1(&self, topology: &Topology) -> Result<Answer, ParseError> {
+        Ok(count_trees_on_slope(topology, Vector::new(3, 1)).into())
+    }
+
+    fn part2(&self, topology: &Topology) -> Result<Answer, ParseError> {
+        let slopes = vec![
+            Vector::new(1, 1),
+            Vector::new(3, 1),
+            Vector::new(5, 1),
+            Vector::new(7, 1),
+            Vector::new(1, 2),
+        ];
+        product_of_trees_on_slopes(topology, &slopes)
+            .map(Answer::from)
+            .ok_or_else(|| ParseError::new("", "the product of trees does not fit in 64 bits"))
+    }
 
-pub fn execute() {
-    let topology = read_lines("day3").unwrap().into();
-    println!(
-        "3:1 — Number of trees on slope: {}",
-        count_trees_on_slope(&topology, Slope::new(3, 1)),
-    );
-    let slopes = vec![
-        Slope::new(1, 1),
-        Slope::new(3, 1),
-        Slope::new(5, 1),
-        Slope::new(7, 1),
-        Slope::new(1, 2),
-    ];
-    println!(
-        "3:1 — Product of trees on all slopes: {}",
-        product_of_trees_on_slopes(&topology, &slopes),
-    );
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_topology(random, size))
+    }
 }
 
-fn count_trees_on_slope(topology: &Topology, slope: Slope) -> usize {
-    TobogganDescent::new(topology, slope).filter(|r| *r).count()
+static EXAMPLES: &[Example] = &[Example {
+    input: r"..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#",
+    part1: Some(7),
+    part2: Some(336),
+    solver: None,
+}];
+
+fn get_topology(input: &str) -> Result<Topology, ParseError> {
+    let grid = Grid::parse(input)?;
+    if grid.height() == 0 {
+        Err(ParseError::new("", "empty map"))
+    } else {
+        Ok(Topology { grid })
+    }
 }
 
-fn product_of_trees_on_slopes(topology: &Topology, slopes: &[Slope]) -> usize {
-    slopes
-        .iter()
-        .map(|slope| count_trees_on_slope(topology, *slope))
-        .product()
+fn count_trees_on_slope(topology: &Topology, slope: Vector) -> usize {
+    TobogganDescent::new(topology, slope).filter(|r| *r).count()
 }
 
-struct Topology {
-    width: usize,
-    trees: Vec<TopologyLine>,
+fn product_of_trees_on_slopes(topology: &Topology, slopes: &[Vector]) -> Option<u64> {
+    slopes.iter().try_fold(1u64, |product, slope| {
+        product.checked_mul(count_trees_on_slope(topology, *slope) as u64)
+    })
 }
 
-#[derive(Clone)]
-struct TopologyLine(Vec<usize>);
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Square {
+    Open,
+    Tree,
+}
 
-#[derive(Copy, Clone)]
-struct Position {
-    x: usize,
-    y: usize,
+impl Cell for Square {
+    const CHARS: &'static [(char, Self)] = &[(OPEN_CHAR, Square::Open), (TREE_CHAR, Square::Tree)];
 }
 
-#[derive(Copy, Clone)]
-struct Slope {
-    right: usize,
-    down: usize,
+pub struct Topology {
+    grid: Grid<Square>,
 }
 
 struct TobogganDescent<'a> {
     topology: &'a Topology,
-    slope: Slope,
-    position: Position,
+    slope: Vector,
+    position: Vector,
 }
 
 impl Topology {
     fn width(&self) -> usize {
-        self.width
+        self.grid.width()
     }
 
     fn height(&self) -> usize {
-        self.trees.len()
-    }
-
-    fn has_tree(&self, position: &Position) -> bool {
-        self.trees
-            .get(position.y)
-            .map(|line| line.has_tree(position.x))
-            .unwrap_or(false)
-    }
-}
-
-impl From<Vec<String>> for Topology {
-    fn from(lines: Vec<String>) -> Self {
-        Self {
-            width: lines.get(0).map(|line| line.len()).unwrap_or(0),
-            trees: lines.into_iter().map(|line| line.into()).collect(),
-        }
+        self.grid.height()
     }
-}
 
-impl TopologyLine {
-    fn has_tree(&self, position: usize) -> bool {
-        self.0.contains(&position)
-    }
-}
-
-impl From<String> for TopologyLine {
-    fn from(line: String) -> Self {
-        Self(
-            line.chars()
-                .enumerate()
-                .filter(|(_, c)| *c == TREE_CHAR)
-                .map(|(i, _)| i)
-                .collect(),
-        )
+    /// The map repeats itself to the right
+    fn has_tree(&self, position: Vector) -> bool {
+        let x = position.x.rem_euclid(self.width() as isize);
+        self.grid.get(Vector::new(x, position.y)) == Some(&Square::Tree)
     }
 }
 
 impl<'a> TobogganDescent<'a> {
-    fn new(topology: &'a Topology, slope: Slope) -> Self {
+    fn new(topology: &'a Topology, slope: Vector) -> Self {
         Self {
             topology,
             slope,
-            position: Position::initial(),
+            position: Vector::ORIGIN,
         }
     }
 }
@@ -118,47 +140,42 @@ impl<'a> Iterator for TobogganDescent<'a> {
     type Item = bool;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let result = if self.position.y > self.topology.height() {
+        let result = if self.position.y > self.topology.height() as isize {
             None
         } else {
-            Some(self.topology.has_tree(&self.position))
+            Some(self.topology.has_tree(self.position))
         };
-        self.position = (&self.position + &self.slope) % self.topology.width();
+        self.position += self.slope;
         result
     }
 }
 
-impl Slope {
-    fn new(right: usize, down: usize) -> Self {
-        Self { right, down }
-    }
-}
-
-impl Position {
-    fn initial() -> Self {
-        Self { x: 0, y: 0 }
-    }
-}
-
-impl Add<&Slope> for &Position {
-    type Output = Position;
-
-    fn add(self, slope: &Slope) -> Self::Output {
-        Position {
-            x: self.x + slope.right,
-            y: self.y + slope.down,
-        }
-    }
-}
-
-impl Rem<usize> for Position {
-    type Output = Position;
-
-    fn rem(self, width: usize) -> Self::Output {
-        Position {
-            x: self.x % width,
-            y: self.y,
-        }
+/// As wide as the personal inputs, `size` rows high
+fn generate_topology(random: &mut Random, size: usize) -> Generated {
+    const WIDTH: usize = 31;
+    let rows = (0..size.max(1))
+        .map(|_| (0..WIDTH).map(|_| random.chance(1, 5)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let trees = |right: usize, down: usize| {
+        (0..rows.len())
+            .step_by(down)
+            .enumerate()
+            .filter(|(step, y)| rows[*y][step * right % WIDTH])
+            .count() as i128
+    };
+    let input = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|tree| if *tree { TREE_CHAR } else { OPEN_CHAR })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Generated {
+        input,
+        part1: Some(trees(3, 1)),
+        part2: Some(trees(1, 1) * trees(3, 1) * trees(5, 1) * trees(7, 1) * trees(1, 2)),
     }
 }
 
@@ -168,8 +185,8 @@ mod count_trees_on_slope_should {
 
     #[test]
     fn return_0_when_first_line_has_no_trees_and_there_is_one_line() {
-        let topology = vec![".....".into()].into();
-        let slope = Slope::new(1, 1);
+        let topology = get_topology(".....").unwrap();
+        let slope = Vector::new(1, 1);
 
         let result = count_trees_on_slope(&topology, slope);
 
@@ -178,8 +195,8 @@ mod count_trees_on_slope_should {
 
     #[test]
     fn return_1_when_first_line_has_a_tree_in_first_position_and_there_is_one_line() {
-        let topology = vec!["#....".into()].into();
-        let slope = Slope::new(1, 1);
+        let topology = get_topology("#....").unwrap();
+        let slope = Vector::new(1, 1);
 
         let result = count_trees_on_slope(&topology, slope);
 
@@ -188,8 +205,8 @@ mod count_trees_on_slope_should {
 
     #[test]
     fn return_0_when_first_position_and_second_position_have_no_trees_and_there_are_two_lines() {
-        let topology = vec!["......".into(), "......".into()].into();
-        let slope = Slope::new(1, 1);
+        let topology = get_topology("......\n......").unwrap();
+        let slope = Vector::new(1, 1);
 
         let result = count_trees_on_slope(&topology, slope);
 
@@ -199,8 +216,8 @@ mod count_trees_on_slope_should {
     #[test]
     fn return_1_when_first_position_has_tree_and_second_position_has_no_tree_and_there_are_two_lines(
     ) {
-        let topology = vec!["#.....".into(), "......".into()].into();
-        let slope = Slope::new(1, 1);
+        let topology = get_topology("#.....\n......").unwrap();
+        let slope = Vector::new(1, 1);
 
         let result = count_trees_on_slope(&topology, slope);
 
@@ -209,8 +226,8 @@ mod count_trees_on_slope_should {
 
     #[test]
     fn return_2_when_first_position_and_second_position_have_trees_and_there_are_two_lines() {
-        let topology = vec!["#.....".into(), ".#....".into()].into();
-        let slope = Slope::new(1, 1);
+        let topology = get_topology("#.....\n.#....").unwrap();
+        let slope = Vector::new(1, 1);
 
         let result = count_trees_on_slope(&topology, slope);
 
@@ -219,14 +236,8 @@ mod count_trees_on_slope_should {
 
     #[test]
     fn return_1_when_there_is_a_tree_on_2_2_slope_is_4_2_and_width_is_8() {
-        let topology = vec![
-            "......".into(),
-            "......".into(),
-            "..#...".into(),
-            "......".into(),
-        ]
-        .into();
-        let slope = Slope::new(4, 1);
+        let topology = get_topology("......\n......\n..#...\n......").unwrap();
+        let slope = Vector::new(4, 1);
 
         let result = count_trees_on_slope(&topology, slope);
 
@@ -235,24 +246,23 @@ mod count_trees_on_slope_should {
 
     #[test]
     fn return_7_for_the_example() {
-        let topology = vec![
-            "..##.......".into(),
-            "#...#...#..".into(),
-            ".#....#..#.".into(),
-            "..#.#...#.#".into(),
-            ".#...##..#.".into(),
-            "..#.##.....".into(),
-            ".#.#.#....#".into(),
-            ".#........#".into(),
-            "#.##...#...".into(),
-            "#...##....#".into(),
-            ".#..#...#.#".into(),
-        ]
-        .into();
-        let slope = Slope::new(3, 1);
+        let topology = get_topology("..##.......\n#...#...#..\n.#....#..#.\n..#.#...#.#\n.#...##..#.\n..#.##.....\n.#.#.#....#\n.#........#\n#.##...#...\n#...##....#\n.#..#...#.#").unwrap();
+        let slope = Vector::new(3, 1);
 
         let result = count_trees_on_slope(&topology, slope);
 
         assert_eq!(result, 7);
     }
 }
+
+#[cfg(test)]
+mod part2_should {
+    use super::*;
+
+    #[test]
+    fn fail_when_the_product_of_trees_overflows() {
+        let topology = Day3.parse(&"#\n".repeat(10000)).unwrap();
+
+        assert!(Day3.part2(&topology).is_err());
+    }
+}
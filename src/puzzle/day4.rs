@@ -1,22 +1,114 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use super::input::read_lines;
+use super::{Answer, Example, Generated, Solution};
+use crate::error::ParseError;
+use crate::puzzle::input::{parse_groups, Group};
+use crate::random::Random;
 use std::collections::HashMap;
 use std::ops::RangeBounds;
 
 const KEY_VALUE_SEPARATOR: char = ':';
 
-pub fn execute() {
-    let passports = parse_passports(read_lines("day4").unwrap());
-    println!(
-        "4:1 — Number of valid passports: {}",
-        count_valid_passports(&passports)
-    );
-    println!(
-        "4:2 — Number of fully valid passports: {}",
-        count_fully_valid_passports(&passports)
-    );
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Model = Vec<Passport>;
+
+    fn day(&self) -> u8 {
+        4
+    }
+
+    fn title(&self) -> &'static str {
+        "Passport Processing"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Number of valid passports",
+            "Number of fully valid passports",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<Passport>, ParseError> {
+        get_passports(input)
+    }
+
+    fn part1(&self, passports: &Vec<Passport>) -> Result<Answer, ParseError> {
+        Ok(count_valid_passports(passports).into())
+    }
+
+    fn part2(&self, passports: &Vec<Passport>) -> Result<Answer, ParseError> {
+        Ok(count_fully_valid_passports(passports).into())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_passports(random, size))
+    }
+}
+
+static EXAMPLES: &[Example] = &[
+    Example {
+        input: r"ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in",
+        part1: Some(2),
+        part2: None,
+        solver: None,
+    },
+    Example {
+        input: r"eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533040 byr:1946
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378 byr:2007",
+        part1: None,
+        part2: Some(0),
+        solver: None,
+    },
+    Example {
+        input: r"pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015 cid:88
+pid:545766238 ecl:hzl
+eyr:2022
+
+iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719",
+        part1: None,
+        part2: Some(4),
+        solver: None,
+    },
+];
+
+fn get_passports(input: &str) -> Result<Vec<Passport>, ParseError> {
+    parse_passports(input)
 }
 
 fn count_valid_passports(passports: &[Passport]) -> usize {
@@ -37,7 +129,7 @@ fn count_fully_valid_passports(passports: &[Passport]) -> usize {
 
 type Passport = HashMap<String, String>;
 
-fn is_fully_valid(passport: &Passport) -> bool {
+pub(crate) fn is_fully_valid(passport: &Passport) -> bool {
     is_valid(passport)
         && passport
             .iter()
@@ -115,138 +207,91 @@ fn is_valid(passport: &Passport) -> bool {
     }
 }
 
-fn parse_passports(lines: Vec<String>) -> Vec<Passport> {
-    group_by_passport(lines)
-        .into_iter()
-        .map(parse_passport)
-        .collect()
-}
-
-fn parse_passport(passport_lines: Vec<String>) -> Passport {
-    passport_lines
-        .iter()
-        .map(|line| {
-            line.split(' ').map(|field| {
-                let entry = field.splitn(2, KEY_VALUE_SEPARATOR).collect::<Vec<_>>();
-                (entry[0].into(), entry[1].into())
-            })
-        })
-        .flatten()
-        .collect()
+pub(crate) fn parse_passports(input: &str) -> Result<Vec<Passport>, ParseError> {
+    parse_groups(input, parse_passport)
 }
 
-fn group_by_passport(lines: Vec<String>) -> Vec<Vec<String>> {
-    let mut passports = Vec::new();
-    let mut accumulator = Vec::new();
-    for line in lines {
-        if line.is_empty() {
-            passports.push(accumulator);
-            accumulator = Vec::new();
-        } else {
-            accumulator.push(line)
+fn parse_passport(group: &Group) -> Result<Passport, ParseError> {
+    let mut passport = Passport::new();
+    for (n, line) in group.numbered_lines() {
+        for field in line.split_whitespace() {
+            let (key, value) = field
+                .split_once(KEY_VALUE_SEPARATOR)
+                .ok_or_else(|| ParseError::new(field, "expected a key:value field").at_line(n))?;
+            passport.insert(key.into(), value.into());
         }
     }
-    if !accumulator.is_empty() {
-        passports.push(accumulator);
-    }
-    passports
+    Ok(passport)
 }
 
-#[cfg(test)]
-mod group_by_passport_should {
-    use super::*;
-
-    #[test]
-    fn return_a_vec_of_size_one_when_there_is_only_one_line() {
-        let lines: Vec<String> = vec!["ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".into()];
-
-        let result = group_by_passport(lines);
-
-        assert_eq!(
-            result,
-            vec![vec![
-                "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".to_string()
-            ]]
-        );
-    }
-
-    #[test]
-    fn return_a_vec_of_size_two_when_there_is_only_two_one_line_passports() {
-        let lines: Vec<String> = vec![
-            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".into(),
-            "".into(),
-            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".into(),
-        ];
-
-        let result = group_by_passport(lines);
+const REQUIRED_FIELDS: [&str; 7] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
 
-        assert_eq!(
-            result,
-            vec![
-                vec!["ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".to_string()],
-                vec!["ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".to_string()],
-            ]
-        );
+/// A quarter of the passports miss a required field, and a quarter of the others have one invalid
+/// value
+fn generate_passports(random: &mut Random, size: usize) -> Generated {
+    let (mut valid, mut fully_valid) = (0, 0);
+    let mut passports = Vec::with_capacity(size);
+    for _ in 0..size {
+        let mut fields = REQUIRED_FIELDS
+            .iter()
+            .map(|key| (*key, generate_field(random, key)))
+            .collect::<Vec<_>>();
+        if random.chance(1, 2) {
+            fields.push(("cid", random.between(1, 999).to_string()));
+        }
+        let missing = random.chance(1, 4);
+        let invalid = random.chance(1, 4);
+        if missing {
+            fields.remove(random.index(REQUIRED_FIELDS.len()));
+        } else {
+            valid += 1;
+            if invalid {
+                let index = random.index(REQUIRED_FIELDS.len());
+                fields[index].1 = generate_invalid_field(random, fields[index].0);
+            } else {
+                fully_valid += 1;
+            }
+        }
+        random.shuffle(&mut fields);
+        let mut passport = String::new();
+        for (i, (key, value)) in fields.iter().enumerate() {
+            if i > 0 {
+                passport.push(if random.chance(1, 4) { '\n' } else { ' ' });
+            }
+            passport.push_str(&format!("{}{}{}", key, KEY_VALUE_SEPARATOR, value));
+        }
+        passports.push(passport);
     }
-
-    #[test]
-    fn return_a_vec_of_size_one_when_there_is_one_passport_on_two_lines() {
-        let lines: Vec<String> = vec![
-            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".into(),
-            "byr:1937 iyr:2017 cid:147 hgt:183cm".into(),
-        ];
-
-        let result = group_by_passport(lines);
-
-        assert_eq!(
-            result,
-            vec![vec![
-                "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".to_string(),
-                "byr:1937 iyr:2017 cid:147 hgt:183cm".to_string(),
-            ]]
-        );
+    Generated {
+        input: passports.join("\n\n"),
+        part1: Some(valid),
+        part2: Some(fully_valid),
     }
+}
 
-    #[test]
-    fn ignore_a_last_empty_line() {
-        let lines: Vec<String> = vec![
-            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".into(),
-            "byr:1937 iyr:2017 cid:147 hgt:183cm".into(),
-            "".into(),
-        ];
-
-        let result = group_by_passport(lines);
-
-        assert_eq!(
-            result,
-            vec![vec![
-                "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".to_string(),
-                "byr:1937 iyr:2017 cid:147 hgt:183cm".to_string(),
-            ]]
-        );
+fn generate_field(random: &mut Random, key: &str) -> String {
+    match key {
+        "byr" => random.between(1920, 2002).to_string(),
+        "iyr" => random.between(2010, 2020).to_string(),
+        "eyr" => random.between(2020, 2030).to_string(),
+        "hgt" if random.chance(1, 2) => format!("{}cm", random.between(150, 193)),
+        "hgt" => format!("{}in", random.between(59, 76)),
+        "hcl" => format!("#{:06x}", random.below(1 << 24)),
+        "ecl" => random.choose(&VALID_EYE_COLORS).to_string(),
+        _ => format!("{:09}", random.below(1_000_000_000)),
     }
+}
 
-    #[test]
-    fn return_a_vec_of_size_two_when_there_is_only_two_multiline_passports() {
-        let lines: Vec<String> = vec![
-            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".into(),
-            "byr:1937 iyr:2017 cid:147 hgt:183cm".into(),
-            "".into(),
-            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".into(),
-        ];
-
-        let result = group_by_passport(lines);
-
-        assert_eq!(
-            result,
-            vec![
-                vec![
-                    "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".to_string(),
-                    "byr:1937 iyr:2017 cid:147 hgt:183cm".to_string(),
-                ],
-                vec!["ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".into()],
-            ]
-        );
+fn generate_invalid_field(random: &mut Random, key: &str) -> String {
+    match key {
+        "byr" => random.between(1900, 1919).to_string(),
+        "iyr" => random.between(2021, 2030).to_string(),
+        "eyr" => random.between(2000, 2019).to_string(),
+        "hgt" if random.chance(1, 2) => format!("{}in", random.between(150, 193)),
+        "hgt" => random.between(150, 193).to_string(),
+        "hcl" => format!("{:06x}", random.below(1 << 24)),
+        "ecl" => random.choose(&["gmt", "zzz", "xry"]).to_string(),
+        _ => format!("{:010}", random.below(10_000_000_000)),
     }
 }
 
@@ -254,11 +299,18 @@ mod group_by_passport_should {
 mod parse_passport_should {
     use super::*;
 
+    fn group<'a>(lines: &[&'a str]) -> Group<'a> {
+        Group {
+            first_line: 1,
+            lines: lines.to_vec(),
+        }
+    }
+
     #[test]
     fn return_a_passport_with_one_field_when_there_is_one_line_with_one_field() {
-        let passport_lines = vec!["ecl:gry".into()];
+        let passport_lines = ["ecl:gry"];
 
-        let result = parse_passport(passport_lines);
+        let result = parse_passport(&group(&passport_lines)).unwrap();
 
         assert_eq!(
             result,
@@ -268,9 +320,9 @@ mod parse_passport_should {
 
     #[test]
     fn return_a_passport_with_two_fields_when_there_is_one_line_with_two_fields() {
-        let passport_lines = vec!["ecl:gry pid:860033327".into()];
+        let passport_lines = ["ecl:gry pid:860033327"];
 
-        let result = parse_passport(passport_lines);
+        let result = parse_passport(&group(&passport_lines)).unwrap();
 
         assert_eq!(
             result,
@@ -285,9 +337,9 @@ mod parse_passport_should {
 
     #[test]
     fn return_a_passport_with_two_fields_when_there_is_two_lines_with_one_field_each() {
-        let passport_lines = vec!["ecl:gry".into(), "pid:860033327".into()];
+        let passport_lines = ["ecl:gry", "pid:860033327"];
 
-        let result = parse_passport(passport_lines);
+        let result = parse_passport(&group(&passport_lines)).unwrap();
 
         assert_eq!(
             result,
@@ -302,12 +354,12 @@ mod parse_passport_should {
 
     #[test]
     fn return_a_passport_with_8_fields_when_there_is_two_lines_with_four_field_each() {
-        let passport_lines = vec![
-            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd".into(),
-            "byr:1937 iyr:2017 cid:147 hgt:183cm".into(),
+        let passport_lines = [
+            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd",
+            "byr:1937 iyr:2017 cid:147 hgt:183cm",
         ];
 
-        let result = parse_passport(passport_lines);
+        let result = parse_passport(&group(&passport_lines)).unwrap();
 
         assert_eq!(
             result,
@@ -327,6 +379,31 @@ mod parse_passport_should {
     }
 }
 
+#[cfg(test)]
+mod parse_passports_should {
+    use super::*;
+
+    #[test]
+    fn locate_an_invalid_field_on_its_line() {
+        let input = "ecl:gry\npid:860033327\n\nbyr:1937\niyr2017 cid:147\n";
+
+        assert_eq!(
+            parse_passports(input),
+            Err(ParseError::new("iyr2017", "expected a key:value field").at_line(5))
+        );
+    }
+
+    #[test]
+    fn split_passports_separated_by_crlf_blank_lines() {
+        let input = "ecl:gry\r\npid:860033327\r\n\r\nbyr:1937\r\n";
+
+        assert_eq!(
+            parse_passports(input).map(|passports| passports.len()),
+            Ok(2)
+        );
+    }
+}
+
 #[cfg(test)]
 mod is_valid_should {
     use super::*;
@@ -569,21 +646,25 @@ mod is_fully_valid_should {
 
     #[test]
     fn return_false_for_invalid_passwords() {
-        let invalid_passports = parse_passports(vec![
-            "eyr:1972 cid:100".into(),
-            "hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926".into(),
-            "".into(),
-            "iyr:2019".into(),
-            "hcl:#602927 eyr:1967 hgt:170cm".into(),
-            "ecl:grn pid:012533040 byr:1946".into(),
-            "".into(),
-            "hcl:dab227 iyr:2012".into(),
-            "ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277".into(),
-            "".into(),
-            "hgt:59cm ecl:zzz".into(),
-            "eyr:2038 hcl:74454a iyr:2023".into(),
-            "pid:3556412378 byr:2007".into(),
-        ]);
+        let invalid_passports = parse_passports(
+            &[
+                "eyr:1972 cid:100",
+                "hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926",
+                "",
+                "iyr:2019",
+                "hcl:#602927 eyr:1967 hgt:170cm",
+                "ecl:grn pid:012533040 byr:1946",
+                "",
+                "hcl:dab227 iyr:2012",
+                "ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277",
+                "",
+                "hgt:59cm ecl:zzz",
+                "eyr:2038 hcl:74454a iyr:2023",
+                "pid:3556412378 byr:2007",
+            ]
+            .join("\n"),
+        )
+        .unwrap();
 
         assert!(invalid_passports
             .iter()
@@ -592,20 +673,24 @@ mod is_fully_valid_should {
 
     #[test]
     fn return_true_for_valid_passwords() {
-        let valid_passports = parse_passports(vec![
-            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980".into(),
-            "hcl:#623a2f".into(),
-            "".into(),
-            "eyr:2029 ecl:blu cid:129 byr:1989".into(),
-            "iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm".into(),
-            "".into(),
-            "hcl:#888785".into(),
-            "hgt:164cm byr:2001 iyr:2015 cid:88".into(),
-            "pid:545766238 ecl:hzl".into(),
-            "eyr:2022".into(),
-            "".into(),
-            "iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719".into(),
-        ]);
+        let valid_passports = parse_passports(
+            &[
+                "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980",
+                "hcl:#623a2f",
+                "",
+                "eyr:2029 ecl:blu cid:129 byr:1989",
+                "iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm",
+                "",
+                "hcl:#888785",
+                "hgt:164cm byr:2001 iyr:2015 cid:88",
+                "pid:545766238 ecl:hzl",
+                "eyr:2022",
+                "",
+                "iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719",
+            ]
+            .join("\n"),
+        )
+        .unwrap();
 
         assert!(valid_passports.iter().all(is_fully_valid));
     }
@@ -1,44 +1,85 @@
-use crate::puzzle::input::read_lines;
+use crate::error::ParseError;
+use crate::puzzle::input::parse_lines;
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
+use std::convert::TryFrom;
 use std::ops::RangeInclusive;
 
-pub fn execute() {
-    let boarding_passes: Vec<BoardingPass> = read_lines("day5")
-        .unwrap()
-        .iter()
-        .map(|line| line.as_str().into())
-        .collect();
-    println!(
-        "5:1 — Highest seat id: {}",
-        highest_seat_id(&boarding_passes),
-    );
-    println!("5:1 — Seat id: {}", find_seat_id(&boarding_passes));
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Model = Vec<BoardingPass>;
+
+    fn day(&self) -> u8 {
+        5
+    }
+
+    fn title(&self) -> &'static str {
+        "Binary Boarding"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        ["Highest seat id", "Seat id"]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<BoardingPass>, ParseError> {
+        get_boarding_passes(input)
+    }
+
+    fn part1(&self, boarding_passes: &Vec<BoardingPass>) -> Result<Answer, ParseError> {
+        highest_seat_id(boarding_passes)
+            .map(Answer::from)
+            .ok_or_else(|| ParseError::new("", "expected at least one boarding pass"))
+    }
+
+    fn part2(&self, boarding_passes: &Vec<BoardingPass>) -> Result<Answer, ParseError> {
+        find_seat_id(boarding_passes)
+            .map(Answer::from)
+            .ok_or_else(|| ParseError::new("", "no free seat between two boarding passes"))
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_boarding_passes(random, size))
+    }
 }
 
-fn highest_seat_id(boarding_passes: &[BoardingPass]) -> u16 {
-    boarding_passes
-        .iter()
-        .map(BoardingPass::seat_id)
-        .max()
-        .unwrap()
+static EXAMPLES: &[Example] = &[Example {
+    input: r"FBFBBFFRLR
+BFFFBBFRRR
+FFFBBBFRRR
+BBFFBBFRLL",
+    part1: Some(820),
+    part2: None,
+    solver: None,
+}];
+
+fn get_boarding_passes(input: &str) -> Result<Vec<BoardingPass>, ParseError> {
+    parse_lines(input, BoardingPass::try_from)
+}
+
+fn highest_seat_id(boarding_passes: &[BoardingPass]) -> Option<u16> {
+    boarding_passes.iter().map(BoardingPass::seat_id).max()
 }
 
-fn find_seat_id(boarding_passes: &[BoardingPass]) -> u16 {
+/// The first seat whose neighbours both have a boarding pass
+fn find_seat_id(boarding_passes: &[BoardingPass]) -> Option<u16> {
     let mut seat_ids = boarding_passes
         .iter()
         .map(BoardingPass::seat_id)
         .collect::<Vec<_>>();
     seat_ids.sort_unstable();
     seat_ids
-        .iter()
-        .enumerate()
-        .find(|(i, seat_id)| seat_ids[i + 1] != *seat_id + 1)
-        .map(|(_, seat_id)| *seat_id)
-        .unwrap()
-        + 1
+        .windows(2)
+        .find(|pair| pair[1] == pair[0] + 2)
+        .map(|pair| pair[0] + 1)
 }
 
 #[derive(Clone, PartialEq, PartialOrd)]
-struct BoardingPass {
+pub struct BoardingPass {
     row: u8,
     column: u8,
 }
@@ -49,12 +90,24 @@ impl BoardingPass {
     }
 }
 
-impl From<&str> for BoardingPass {
-    fn from(input: &str) -> Self {
-        Self {
+impl TryFrom<&str> for BoardingPass {
+    type Error = ParseError;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        let is_valid = input.is_ascii()
+            && input.len() == 10
+            && input[0..7].chars().all(|c| c == 'F' || c == 'B')
+            && input[7..].chars().all(|c| c == 'L' || c == 'R');
+        if !is_valid {
+            return Err(ParseError::new(
+                input,
+                "expected 7 F or B followed by 3 L or R",
+            ));
+        }
+        Ok(Self {
             row: partition_binary_space(&input[0..7], 0..=127),
             column: partition_binary_space(&input[7..], 0..=7),
-        }
+        })
     }
 }
 
@@ -69,12 +122,38 @@ fn partition(range: RangeInclusive<u8>, c: char) -> RangeInclusive<u8> {
             *range.start()..=end
         }
         _ => {
-            let start = (range.start() + range.end() + 1) / 2;
+            let start = (range.start() + range.end()).div_ceil(2);
             start..=*range.end()
         }
     }
 }
 
+/// Consecutive seat ids but one, so there are at most 1021 boarding passes
+fn generate_boarding_passes(random: &mut Random, size: usize) -> Generated {
+    let size = size.clamp(2, 1021) as u64;
+    let lowest = random.between(1, 1022 - size);
+    let highest = lowest + size;
+    let missing = random.between(lowest + 1, highest - 1);
+    let mut passes = (lowest..=highest)
+        .filter(|id| *id != missing)
+        .map(|id| {
+            let row = (0..7)
+                .rev()
+                .map(|bit| if id >> (bit + 3) & 1 == 1 { 'B' } else { 'F' });
+            let column = (0..3)
+                .rev()
+                .map(|bit| if id >> bit & 1 == 1 { 'R' } else { 'L' });
+            row.chain(column).collect::<String>()
+        })
+        .collect::<Vec<_>>();
+    random.shuffle(&mut passes);
+    Generated {
+        input: passes.join("\n"),
+        part1: Some(highest as i128),
+        part2: Some(missing as i128),
+    }
+}
+
 #[cfg(test)]
 mod partition_should {
     use super::*;
@@ -119,3 +198,58 @@ mod partition_binary_space_should {
         assert_eq!(partition_binary_space("RLR", 0..=7), 5)
     }
 }
+
+#[cfg(test)]
+mod boarding_pass_should {
+    use super::*;
+
+    #[test]
+    fn have_the_seat_id_of_its_row_and_column() {
+        assert_eq!(
+            BoardingPass::try_from("FBFBBFFRLR").map(|b| b.seat_id()),
+            Ok(357)
+        );
+    }
+
+    #[test]
+    fn reject_a_pass_with_a_multi_byte_char() {
+        assert!(BoardingPass::try_from("FBFBBFéRL").is_err());
+    }
+}
+
+#[cfg(test)]
+mod highest_seat_id_should {
+    use super::*;
+
+    #[test]
+    fn return_nothing_without_boarding_passes() {
+        assert_eq!(highest_seat_id(&[]), None);
+    }
+}
+
+#[cfg(test)]
+mod find_seat_id_should {
+    use super::*;
+
+    fn passes(lines: &[&str]) -> Vec<BoardingPass> {
+        lines
+            .iter()
+            .map(|line| BoardingPass::try_from(*line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn return_the_seat_between_two_boarding_passes() {
+        let passes = passes(&["FBFBBFFRRR", "FBFBBFFRLR", "FBFBBFFLLL"]);
+
+        assert_eq!(find_seat_id(&passes), Some(358));
+    }
+
+    #[test]
+    fn return_nothing_when_the_seats_have_no_gap() {
+        let passes = passes(&["FBFBBFFRLR", "FBFBBFFRRL"]);
+
+        assert_eq!(find_seat_id(&passes), None);
+        assert_eq!(find_seat_id(&[]), None);
+    }
+}
@@ -1,33 +1,86 @@
 use std::collections::HashSet;
 
-use lazy_static::lazy_static;
-use regex::Regex;
-
-use crate::puzzle::input::read_input;
-
-pub fn execute() {
-    let input = read_input("day6");
-    let groups = get_groups(input.as_str());
-    println!(
-        "6:1 — Sum of unique yes answers in each group: {}",
-        sum_of_unique_yes_answers_in_each_group(&groups),
-    );
-    println!(
-        "6:2 — Sum of yes answers by all members in each group: {}",
-        sum_of_yes_answers_by_all_members_in_each_group(&groups),
-    );
-}
+use crate::error::ParseError;
+use crate::puzzle::input::parse_groups;
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Model = Vec<String>;
+
+    fn day(&self) -> u8 {
+        6
+    }
+
+    fn title(&self) -> &'static str {
+        "Custom Customs"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Sum of unique yes answers in each group",
+            "Sum of yes answers by all members in each group",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, ParseError> {
+        get_groups(input)
+    }
+
+    fn part1(&self, groups: &Vec<String>) -> Result<Answer, ParseError> {
+        Ok(sum_of_unique_yes_answers_in_each_group(groups).into())
+    }
 
-lazy_static! {
-    static ref GROUP_SEPARATOR: Regex = Regex::new(r"\r?\n\s*\r?\n").unwrap();
-    static ref MEMBER_SEPARATOR: Regex = Regex::new(r"\r?\n").unwrap();
+    fn part2(&self, groups: &Vec<String>) -> Result<Answer, ParseError> {
+        Ok(sum_of_yes_answers_by_all_members_in_each_group(groups).into())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_groups(random, size))
+    }
 }
 
-fn get_groups(input: &str) -> Vec<&str> {
-    GROUP_SEPARATOR.split(input).collect()
+static EXAMPLES: &[Example] = &[Example {
+    input: r"abc
+
+a
+b
+c
+
+ab
+ac
+
+a
+a
+a
+a
+
+b",
+    part1: Some(11),
+    part2: Some(6),
+    solver: None,
+}];
+
+fn get_groups(input: &str) -> Result<Vec<String>, ParseError> {
+    parse_groups(input, |group| {
+        group.parse_lines(|line| {
+            if line.trim().chars().all(|c| c.is_ascii_lowercase()) {
+                Ok(())
+            } else {
+                Err(ParseError::new(line, "expected only answers from a to z"))
+            }
+        })?;
+        Ok(group.lines.join("\n"))
+    })
 }
 
-fn sum_of_unique_yes_answers_in_each_group(groups: &[&str]) -> usize {
+fn sum_of_unique_yes_answers_in_each_group(groups: &[String]) -> usize {
     groups
         .iter()
         .map(|group| unique_yes_answers_in_group(group))
@@ -37,14 +90,14 @@ fn sum_of_unique_yes_answers_in_each_group(groups: &[&str]) -> usize {
 fn unique_yes_answers_in_group(group: &str) -> usize {
     let mut chars = group
         .chars()
-        .filter(|c| ('a'..='z').contains(c))
+        .filter(|c: &char| c.is_ascii_lowercase())
         .collect::<Vec<_>>();
     chars.sort_unstable();
     chars.dedup();
     chars.len()
 }
 
-fn sum_of_yes_answers_by_all_members_in_each_group(groups: &[&str]) -> usize {
+fn sum_of_yes_answers_by_all_members_in_each_group(groups: &[String]) -> usize {
     groups
         .iter()
         .map(|group| nb_yes_answers_by_all_group_members(group))
@@ -52,13 +105,13 @@ fn sum_of_yes_answers_by_all_members_in_each_group(groups: &[&str]) -> usize {
 }
 
 fn nb_yes_answers_by_all_group_members(group: &str) -> usize {
-    MEMBER_SEPARATOR
-        .split(group)
+    group
+        .lines()
         .filter(|member| !member.is_empty())
         .map(|member| {
             member
                 .chars()
-                .filter(|c| ('a'..='z').contains(c))
+                .filter(|c: &char| c.is_ascii_lowercase())
                 .collect::<HashSet<_>>()
         })
         .fold(None as Option<HashSet<char>>, |acc, member| {
@@ -70,7 +123,42 @@ fn nb_yes_answers_by_all_group_members(group: &str) -> usize {
             }
         })
         .map(|yes_answers| yes_answers.len())
-        .unwrap()
+        .unwrap_or(0)
+}
+
+/// Members of a group pick their answers among the questions of the group, answers being kept as
+/// bits from a to z
+fn generate_groups(random: &mut Random, size: usize) -> Generated {
+    let (mut anyone, mut everyone) = (0, 0);
+    let mut groups = Vec::with_capacity(size);
+    for _ in 0..size {
+        let questions = random.between(1, (1 << 26) - 1) as u32;
+        let members = (0..random.between(1, 5))
+            .map(|_| match questions & random.next_u64() as u32 {
+                0 => questions & questions.wrapping_neg(),
+                answers => answers,
+            })
+            .collect::<Vec<_>>();
+        anyone += members.iter().fold(0, |a, b| a | b).count_ones();
+        everyone += members.iter().fold(questions, |a, b| a & b).count_ones();
+        let lines = members
+            .iter()
+            .map(|answers| {
+                let mut letters = (0..26)
+                    .filter(|bit| answers >> bit & 1 == 1)
+                    .map(|bit| (b'a' + bit) as char)
+                    .collect::<Vec<_>>();
+                random.shuffle(&mut letters);
+                letters.into_iter().collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        groups.push(lines.join("\n"));
+    }
+    Generated {
+        input: groups.join("\n\n"),
+        part1: Some(anyone as i128),
+        part2: Some(everyone as i128),
+    }
 }
 
 #[cfg(test)]
@@ -133,7 +221,8 @@ a
 a
 
 b",
-        );
+        )
+        .unwrap();
 
         assert_eq!(sum_of_unique_yes_answers_in_each_group(&groups), 11);
     }
@@ -202,7 +291,8 @@ a
 a
 
 b",
-        );
+        )
+        .unwrap();
 
         assert_eq!(sum_of_yes_answers_by_all_members_in_each_group(&groups), 6);
     }
@@ -1,54 +1,226 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use lazy_static::lazy_static;
 use regex::{Captures, Regex};
 
-use crate::puzzle::input::read_lines;
+use crate::error::ParseError;
+use crate::puzzle::input::lines;
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
+use crate::repl::{ReplError, Session};
 
-pub fn execute() {
-    let lines = read_lines("day7").unwrap();
-    let bags = parse_bags(&lines);
-    println!(
-        "7:1 — Number of bags that an contain a shiny gold bag: {}",
-        count_bags_able_to_contain_a_shiny_gold_bag(&bags),
-    );
-    println!(
-        "7:1 — Number of bags contained by shiny gold bag: {}",
-        bags.count_bags_contained_by("shiny gold"),
-    );
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Model = Bags;
+
+    fn day(&self) -> u8 {
+        7
+    }
+
+    fn title(&self) -> &'static str {
+        "Handy Haversacks"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Number of bags that can contain a shiny gold bag",
+            "Number of bags contained by shiny gold bag",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Bags, ParseError> {
+        parse_bags(&lines(input))
+    }
+
+    fn part1(&self, bags: &Bags) -> Result<Answer, ParseError> {
+        Ok(count_bags_able_to_contain_a_shiny_gold_bag(bags).into())
+    }
+
+    fn part2(&self, bags: &Bags) -> Result<Answer, ParseError> {
+        bags.count_bags_contained_by("shiny gold")
+            .map(Answer::from)
+            .ok_or_else(|| ParseError::new("", "the number of bags does not fit in 64 bits"))
+    }
+
+    fn session(&self, bags: Bags) -> Option<Box<dyn Session>> {
+        Some(Box::new(bags))
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_bags(random, size))
+    }
 }
 
-struct Relation<'a> {
-    containing_bag: &'a str,
+static EXAMPLES: &[Example] = &[
+    Example {
+        input: r"light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.",
+        part1: Some(4),
+        part2: Some(32),
+        solver: None,
+    },
+    Example {
+        input: r"shiny gold bags contain 2 dark red bags.
+dark red bags contain 2 dark orange bags.
+dark orange bags contain 2 dark yellow bags.
+dark yellow bags contain 2 dark green bags.
+dark green bags contain 2 dark blue bags.
+dark blue bags contain 2 dark violet bags.
+dark violet bags contain no other bags.",
+        part1: None,
+        part2: Some(126),
+        solver: None,
+    },
+];
+
+struct Relation {
+    containing_bag: String,
     number: u32,
-    contained_bag: &'a str,
+    contained_bag: String,
 }
 
-struct Bags<'a> {
-    relations: Vec<Relation<'a>>,
+pub struct Bags {
+    relations: Vec<Relation>,
 }
 
-impl<'a> Bags<'a> {
+impl Bags {
     fn bags_containing(&self, bag: &str) -> HashSet<&str> {
         self.relations
             .iter()
             .filter(|r| r.contained_bag == bag)
-            .map(|r| r.containing_bag)
+            .map(|r| r.containing_bag.as_str())
             .collect()
     }
 
-    fn count_bags_contained_by(&self, bag: &str) -> u32 {
+    /// None when the number does not fit in 64 bits. Each bag is counted once, after the bags
+    /// it contains, whatever the number of ways to reach it.
+    fn count_bags_contained_by(&self, bag: &str) -> Option<u64> {
+        let mut contents: HashMap<&str, Vec<&Relation>> = HashMap::new();
+        for r in &self.relations {
+            contents
+                .entry(r.containing_bag.as_str())
+                .or_default()
+                .push(r);
+        }
+        let mut counts: HashMap<&str, u64> = HashMap::new();
+        let mut to_count = vec![(bag, false)];
+        while let Some((bag, expanded)) = to_count.pop() {
+            if counts.contains_key(bag) {
+                continue;
+            }
+            let inner_bags = contents.get(bag).map_or(&[][..], Vec::as_slice);
+            if expanded {
+                let count = inner_bags.iter().try_fold(0u64, |total, r| {
+                    let inside = counts[r.contained_bag.as_str()];
+                    let number = u64::from(r.number);
+                    total.checked_add(number.checked_mul(inside.checked_add(1)?)?)
+                })?;
+                counts.insert(bag, count);
+            } else {
+                to_count.push((bag, true));
+                to_count.extend(inner_bags.iter().map(|r| (r.contained_bag.as_str(), false)));
+            }
+        }
+        counts.get(bag).copied()
+    }
+
+    /// A bag that contains itself, directly or not. The bags that no remaining bag contains are
+    /// removed until none is left, then the remaining containers lead back to a bag of a cycle.
+    fn bag_in_a_cycle(&self) -> Option<&str> {
+        let mut containers = HashMap::new();
+        let mut contents: HashMap<&str, Vec<&str>> = HashMap::new();
+        for r in &self.relations {
+            containers.entry(r.containing_bag.as_str()).or_insert(0);
+            *containers.entry(r.contained_bag.as_str()).or_insert(0) += 1;
+            contents
+                .entry(r.containing_bag.as_str())
+                .or_default()
+                .push(r.contained_bag.as_str());
+        }
+        let mut free_bags = containers
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(bag, _)| *bag)
+            .collect::<Vec<_>>();
+        while let Some(bag) = free_bags.pop() {
+            containers.remove(bag);
+            for inner_bag in contents.get(bag).into_iter().flatten() {
+                if let Some(count) = containers.get_mut(inner_bag) {
+                    *count -= 1;
+                    if *count == 0 {
+                        free_bags.push(inner_bag);
+                    }
+                }
+            }
+        }
+        let mut bag = containers.keys().min().copied()?;
+        let mut visited = HashSet::new();
+        while visited.insert(bag) {
+            bag = self
+                .relations
+                .iter()
+                .find(|r| {
+                    r.contained_bag == bag && containers.contains_key(r.containing_bag.as_str())
+                })?
+                .containing_bag
+                .as_str();
+        }
+        Some(bag)
+    }
+
+    fn contains(&self, bag: &str) -> bool {
         self.relations
             .iter()
-            .filter(|r| r.containing_bag == bag)
-            .map(|r| r.number + r.number * self.count_bags_contained_by(r.contained_bag))
-            .sum()
+            .any(|r| r.containing_bag == bag || r.contained_bag == bag)
+    }
+}
+
+impl Session for Bags {
+    fn namespace(&self) -> &'static str {
+        "bags"
+    }
+
+    fn commands(&self) -> &'static [&'static str] {
+        &[
+            "contained-by <bag> — number of bags inside a bag",
+            "containing <bag> — bags directly containing a bag",
+        ]
+    }
+
+    fn execute(&mut self, command: &str, arguments: &[String]) -> Result<String, ReplError> {
+        let bag = arguments.first().ok_or(ReplError::MissingArgument("bag"))?;
+        if !self.contains(bag) {
+            return Err(ReplError::InvalidArgument(bag.clone()));
+        }
+        match command {
+            "contained-by" => Ok(self
+                .count_bags_contained_by(bag)
+                .map_or_else(|| "more than 2^64".into(), |count| count.to_string())),
+            "containing" => {
+                let mut bags = self.bags_containing(bag).into_iter().collect::<Vec<_>>();
+                bags.sort_unstable();
+                Ok(bags.join(", "))
+            }
+            _ => Err(ReplError::UnknownCommand(command.into())),
+        }
     }
 }
 
-impl<'a, T> From<T> for Bags<'a>
+impl<T> From<T> for Bags
 where
-    T: IntoIterator<Item = Relation<'a>>,
+    T: IntoIterator<Item = Relation>,
 {
     fn from(relations: T) -> Self {
         Self {
@@ -61,32 +233,50 @@ lazy_static! {
     static ref CONTAINING_BAG_REGEX: Regex =
         Regex::new(r"^(?P<bag_color>\w+ \w+) bags contain (?P<rest>.*)$").unwrap();
     static ref CONTAINED_BAG_REGEX: Regex =
-        Regex::new(r"(?P<number>\d) (?P<bag_color>\w+ \w+) bag").unwrap();
+        Regex::new(r"(?P<number>[0-9]) (?P<bag_color>\w+ \w+) bag").unwrap();
 }
 
 fn count_bags_able_to_contain_a_shiny_gold_bag(bags: &Bags) -> usize {
+    let mut containers: HashMap<&str, Vec<&str>> = HashMap::new();
+    for r in &bags.relations {
+        containers
+            .entry(r.contained_bag.as_str())
+            .or_default()
+            .push(r.containing_bag.as_str());
+    }
     let mut bags_to_traverse = vec!["shiny gold"];
     let mut traversed_bags = HashSet::new();
-    while !bags_to_traverse.is_empty() {
-        let bag = bags_to_traverse.remove(0);
-        bags.bags_containing(bag).iter().for_each(|containing_bag| {
-            if !traversed_bags.contains(containing_bag) {
+    while let Some(bag) = bags_to_traverse.pop() {
+        for containing_bag in containers.get(bag).into_iter().flatten() {
+            if traversed_bags.insert(*containing_bag) {
                 bags_to_traverse.push(*containing_bag);
-                traversed_bags.insert(*containing_bag);
             }
-        });
+        }
     }
     traversed_bags.len()
 }
 
-fn parse_bags(lines: &[String]) -> Bags {
-    lines
+fn parse_bags(lines: &[String]) -> Result<Bags, ParseError> {
+    let mut relations = Vec::new();
+    for (i, line) in lines
         .iter()
-        .map(|line| CONTAINING_BAG_REGEX.captures(&line))
-        .flatten()
-        .flat_map(get_relations)
-        .collect::<Vec<_>>()
-        .into()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+    {
+        let captures = CONTAINING_BAG_REGEX.captures(line).ok_or_else(|| {
+            ParseError::new(
+                line,
+                "expected a rule (e.g. light red bags contain 1 bright white bag.)",
+            )
+            .at_line(i + 1)
+        })?;
+        relations.extend(get_relations(captures));
+    }
+    let bags = Bags::from(relations);
+    match bags.bag_in_a_cycle() {
+        Some(bag) => Err(ParseError::new(bag, "a bag cannot contain itself")),
+        None => Ok(bags),
+    }
 }
 
 fn get_relations(captures: Captures) -> Vec<Relation> {
@@ -95,26 +285,152 @@ fn get_relations(captures: Captures) -> Vec<Relation> {
     CONTAINED_BAG_REGEX
         .captures_iter(rest)
         .map(|c| Relation {
-            containing_bag,
+            containing_bag: containing_bag.into(),
             number: c.name("number").unwrap().as_str().parse().unwrap(),
-            contained_bag: c.name("bag_color").unwrap().as_str(),
+            contained_bag: c.name("bag_color").unwrap().as_str().into(),
         })
         .collect()
 }
 
+const ADJECTIVES: [&str; 18] = [
+    "bright", "clear", "dark", "dim", "dotted", "drab", "dull", "faded", "light", "mirrored",
+    "muted", "pale", "plaid", "posh", "shiny", "striped", "vibrant", "wavy",
+];
+
+const COLORS: [&str; 33] = [
+    "aqua",
+    "beige",
+    "black",
+    "blue",
+    "bronze",
+    "brown",
+    "chartreuse",
+    "coral",
+    "crimson",
+    "cyan",
+    "fuchsia",
+    "gold",
+    "gray",
+    "green",
+    "indigo",
+    "lavender",
+    "lime",
+    "magenta",
+    "maroon",
+    "olive",
+    "orange",
+    "plum",
+    "purple",
+    "red",
+    "salmon",
+    "silver",
+    "tan",
+    "teal",
+    "tomato",
+    "turquoise",
+    "violet",
+    "white",
+    "yellow",
+];
+
+const LAYERS: usize = 8;
+
+/// Bags are spread over layers and only contain bags of the next two layers, so no bag ends up
+/// inside itself. The shiny gold bag is in the second layer, so that some bags contain it and the
+/// bags it contains go down through all the others.
+fn generate_bags(random: &mut Random, size: usize) -> Generated {
+    let size = size.max(1);
+    let layer_start = |layer: usize| ((layer * size).div_ceil(LAYERS)).min(size);
+    let mut names = (0..)
+        .map(bag_name)
+        .filter(|name| name != "shiny gold")
+        .take(size - 1)
+        .collect::<Vec<_>>();
+    random.shuffle(&mut names);
+    let shiny_gold = match (layer_start(1), layer_start(2)) {
+        (start, end) if start < end => start + random.index(end - start),
+        _ => random.index(size),
+    };
+    names.insert(shiny_gold, "shiny gold".into());
+    let mut contents: Vec<Vec<(u64, usize)>> = vec![Vec::new(); size];
+    let mut containing = vec![Vec::new(); size];
+    for (bag, inner_bags) in contents.iter_mut().enumerate() {
+        let layer = bag * LAYERS / size;
+        let (start, end) = (layer_start(layer + 1), layer_start(layer + 3));
+        if start == end {
+            continue;
+        }
+        for _ in 0..random.between(0, 4) {
+            let inner = start + random.index(end - start);
+            if inner_bags.iter().all(|(_, other)| *other != inner) {
+                inner_bags.push((random.between(1, 5), inner));
+                containing[inner].push(bag);
+            }
+        }
+    }
+    let mut able_to_contain = HashSet::new();
+    let mut to_traverse = vec![shiny_gold];
+    while let Some(bag) = to_traverse.pop() {
+        for outer in &containing[bag] {
+            if able_to_contain.insert(*outer) {
+                to_traverse.push(*outer);
+            }
+        }
+    }
+    // Inner bags always come later, so counting backwards reuses their counts
+    let mut counts = vec![0u64; size];
+    for bag in (0..size).rev() {
+        counts[bag] = contents[bag]
+            .iter()
+            .map(|(number, inner)| number * (1 + counts[*inner]))
+            .sum();
+    }
+    let mut rules = contents
+        .iter()
+        .zip(&names)
+        .map(|(inner_bags, name)| {
+            format!("{} bags contain {}.", name, describe(inner_bags, &names))
+        })
+        .collect::<Vec<_>>();
+    random.shuffle(&mut rules);
+    Generated {
+        input: rules.join("\n"),
+        part1: Some(able_to_contain.len() as i128),
+        part2: Some(counts[shiny_gold] as i128),
+    }
+}
+
+/// An adjective and a color, with a number once all the pairs are taken
+fn bag_name(index: usize) -> String {
+    let color = COLORS[index / ADJECTIVES.len() % COLORS.len()];
+    match index / (ADJECTIVES.len() * COLORS.len()) {
+        0 => format!("{} {}", ADJECTIVES[index % ADJECTIVES.len()], color),
+        round => format!(
+            "{} {}{}",
+            ADJECTIVES[index % ADJECTIVES.len()],
+            color,
+            round + 1
+        ),
+    }
+}
+
+fn describe(inner_bags: &[(u64, usize)], names: &[String]) -> String {
+    if inner_bags.is_empty() {
+        return "no other bags".into();
+    }
+    inner_bags
+        .iter()
+        .map(|(number, inner)| match number {
+            1 => format!("1 {} bag", names[*inner]),
+            _ => format!("{} {} bags", number, names[*inner]),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[cfg(test)]
 fn first_example_lines() -> Vec<String> {
-    vec![
-        "light red bags contain 1 bright white bag, 2 muted yellow bags.".into(),
-        "dark orange bags contain 3 bright white bags, 4 muted yellow bags.".into(),
-        "bright white bags contain 1 shiny gold bag.".into(),
-        "muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.".into(),
-        "shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.".into(),
-        "dark olive bags contain 3 faded blue bags, 4 dotted black bags.".into(),
-        "vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.".into(),
-        "faded blue bags contain no other bags.".into(),
-        "dotted black bags contain no other bags.".into(),
-    ]
+    EXAMPLES[0].input.lines().map(String::from).collect()
 }
 
 #[cfg(test)]
@@ -124,7 +440,7 @@ mod count_bags_able_to_contain_a_shiny_gold_bag_should {
     #[test]
     fn return_4_for_the_example_data() {
         let lines = first_example_lines();
-        let bags = parse_bags(&lines);
+        let bags = parse_bags(&lines).unwrap();
 
         assert_eq!(count_bags_able_to_contain_a_shiny_gold_bag(&bags), 4);
     }
@@ -137,9 +453,9 @@ mod bags_count_bags_contained_by_should {
     #[test]
     fn return_32_for_the_first_example() {
         let lines = first_example_lines();
-        let bags = parse_bags(&lines);
+        let bags = parse_bags(&lines).unwrap();
 
-        assert_eq!(bags.count_bags_contained_by("shiny gold"), 32);
+        assert_eq!(bags.count_bags_contained_by("shiny gold"), Some(32));
     }
 
     #[test]
@@ -153,8 +469,174 @@ mod bags_count_bags_contained_by_should {
             "dark blue bags contain 2 dark violet bags.".into(),
             "dark violet bags contain no other bags.".into(),
         ];
-        let bags = parse_bags(&lines);
+        let bags = parse_bags(&lines).unwrap();
+
+        assert_eq!(bags.count_bags_contained_by("shiny gold"), Some(126));
+    }
+}
+
+#[cfg(test)]
+mod parse_bags_should {
+    use super::*;
+
+    #[test]
+    fn locate_an_invalid_rule_on_its_line() {
+        let lines = vec![
+            "faded blue bags contain no other bags.".into(),
+            "dotted black bags are empty".into(),
+        ];
+
+        assert_eq!(
+            parse_bags(&lines).err().and_then(|error| error.line),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn reject_bags_containing_themselves() {
+        let lines = vec![
+            "shiny gold bags contain 1 dark red bag.".into(),
+            "dark red bags contain 2 dark orange bags, 1 faded blue bag.".into(),
+            "dark orange bags contain 1 dark red bag.".into(),
+            "faded blue bags contain no other bags.".into(),
+        ];
+
+        assert_eq!(
+            parse_bags(&lines).err(),
+            Some(ParseError::new(
+                "dark orange",
+                "a bag cannot contain itself"
+            ))
+        );
+    }
+
+    #[test]
+    fn ignore_a_number_that_is_not_ascii() {
+        let lines = vec!["shiny gold bags contain ٣ dark red bags.".into()];
+
+        assert_eq!(
+            parse_bags(&lines).map(|bags| bags.count_bags_contained_by("shiny gold")),
+            Ok(Some(0))
+        );
+    }
+}
+
+#[cfg(test)]
+mod part2_should {
+    use super::*;
+
+    #[test]
+    fn fail_when_the_number_of_bags_overflows() {
+        let lines = COLORS
+            .windows(2)
+            .map(|pair| format!("dark {} bags contain 9 dark {} bags.", pair[0], pair[1]))
+            .chain(Some("shiny gold bags contain 9 dark aqua bags.".into()))
+            .collect::<Vec<_>>();
+        let bags = parse_bags(&lines).unwrap();
 
-        assert_eq!(bags.count_bags_contained_by("shiny gold"), 126);
+        assert!(Day7.part2(&bags).is_err());
+    }
+
+    #[test]
+    fn count_each_bag_once_however_many_ways_lead_to_it() {
+        let names = (0..60)
+            .map(bag_name)
+            .filter(|name| name != "shiny gold")
+            .collect::<Vec<_>>();
+        let lines = Some("shiny gold".to_string())
+            .iter()
+            .chain(&names)
+            .zip(names.windows(2))
+            .map(|(bag, inner)| {
+                format!(
+                    "{} bags contain 1 {} bag, 1 {} bag.",
+                    bag, inner[0], inner[1]
+                )
+            })
+            .collect::<Vec<_>>();
+        let bags = parse_bags(&lines).unwrap();
+        let (mut last, mut before_last) = (0u64, 0u64);
+        for _ in 0..lines.len() {
+            let count = last + before_last + 2;
+            before_last = last;
+            last = count;
+        }
+
+        assert_eq!(Day7.part2(&bags), Ok(Answer::from(last)));
+    }
+}
+
+#[cfg(test)]
+mod bags_session_should {
+    use super::*;
+    use crate::repl::run_command;
+
+    #[test]
+    fn count_the_bags_contained_by_a_bag() {
+        let mut bags = parse_bags(&first_example_lines()).unwrap();
+
+        assert_eq!(
+            run_command(&mut bags, r#"bags contained-by "shiny gold""#),
+            Ok("32".into())
+        );
+    }
+
+    #[test]
+    fn list_the_bags_directly_containing_a_bag() {
+        let mut bags = parse_bags(&first_example_lines()).unwrap();
+
+        assert_eq!(
+            run_command(&mut bags, r#"bags containing "shiny gold""#),
+            Ok("bright white, muted yellow".into())
+        );
+    }
+
+    #[test]
+    fn reject_an_unknown_bag() {
+        let mut bags = parse_bags(&first_example_lines()).unwrap();
+
+        assert_eq!(
+            run_command(&mut bags, "bags containing plaid"),
+            Err(ReplError::InvalidArgument("plaid".into()))
+        );
+    }
+}
+
+#[cfg(test)]
+mod generate_bags_should {
+    use super::*;
+
+    #[test]
+    fn describe_each_bag_once_without_cycle() {
+        let generated = generate_bags(&mut Random::new(7), 300);
+        let bags = Day7.parse(&generated.input).unwrap();
+        let mut remaining = bags
+            .relations
+            .iter()
+            .map(|r| r.containing_bag.as_str())
+            .collect::<HashSet<_>>();
+        let rules = generated.input.lines().count();
+
+        assert_eq!(rules, 300);
+        assert!(generated
+            .input
+            .lines()
+            .any(|rule| rule.starts_with("shiny gold bags contain")));
+        // Removing the bags contained by no remaining bag empties an acyclic graph
+        while !remaining.is_empty() {
+            let outermost = remaining
+                .iter()
+                .copied()
+                .filter(|bag| {
+                    !bags.relations.iter().any(|r| {
+                        remaining.contains(r.containing_bag.as_str()) && r.contained_bag == *bag
+                    })
+                })
+                .collect::<Vec<_>>();
+            assert!(!outermost.is_empty(), "The bags contain each other");
+            for bag in outermost {
+                remaining.remove(bag);
+            }
+        }
     }
 }
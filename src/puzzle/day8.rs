@@ -1,43 +1,102 @@
+use crate::error::ParseError;
 use crate::puzzle::day8::ExecutionResult::{Finished, InfiniteLoop};
-use crate::puzzle::input::read_lines;
+use crate::puzzle::input::{parse_lines, parse_number};
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
+use crate::repl::{parse_argument, ReplError, Session};
+use crate::trace::trace;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
 use Instruction::{Accumulator, Jump, Noop};
 
-pub fn execute() {
-    let instructions = parse_instructions(read_lines("day8").unwrap());
-    println!(
-        "8:1 — Value of accumulator before looping: {:?}",
-        Program::new(&instructions).execute(),
-    );
-    println!(
-        "8:2 — Value of accumulator after the fixed program terminates: {:?}",
-        fix_program(&instructions),
-    );
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Model = Vec<Instruction>;
+
+    fn day(&self) -> u8 {
+        8
+    }
+
+    fn title(&self) -> &'static str {
+        "Handheld Halting"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "Value of accumulator before looping",
+            "Value of accumulator after the fixed program terminates",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<Instruction>, ParseError> {
+        parse_instructions(input)
+    }
+
+    fn part1(&self, instructions: &Vec<Instruction>) -> Result<Answer, ParseError> {
+        Ok(Program::new(instructions).execute().accumulator().into())
+    }
+
+    fn part2(&self, instructions: &Vec<Instruction>) -> Result<Answer, ParseError> {
+        fix_program(instructions)
+            .map(|result| result.accumulator().into())
+            .ok_or_else(|| ParseError::new("", "no patched instruction makes the program finish"))
+    }
+
+    fn session(&self, instructions: Vec<Instruction>) -> Option<Box<dyn Session>> {
+        Some(Box::new(Program::new(&instructions)))
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        Some(generate_program(random, size))
+    }
 }
 
-fn parse_instructions(lines: Vec<String>) -> Vec<Instruction> {
-    lines
-        .into_iter()
-        .filter(|line| !line.is_empty())
-        .map(parse_instruction)
-        .collect()
+static EXAMPLES: &[Example] = &[Example {
+    input: r"nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6",
+    part1: Some(5),
+    part2: Some(8),
+    solver: None,
+}];
+
+fn parse_instructions(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    parse_lines(input, parse_instruction)
 }
 
-fn parse_instruction(line: String) -> Instruction {
-    let parameter = line[4..].parse().unwrap();
-    match &line[..3] {
-        "acc" => Accumulator(parameter),
-        "jmp" => Jump(parameter),
-        _ => Noop(parameter),
+pub(crate) fn parse_instruction(line: &str) -> Result<Instruction, ParseError> {
+    let (operation, argument) = line
+        .split_once(' ')
+        .ok_or_else(|| ParseError::new(line, "expected an operation and an argument"))?;
+    let parameter = parse_number(argument)?;
+    match operation {
+        "acc" => Ok(Accumulator(parameter)),
+        "jmp" => Ok(Jump(parameter)),
+        "nop" => Ok(Noop(parameter)),
+        _ => Err(ParseError::new(line, "unknown operation (acc, jmp or nop)")),
     }
 }
 
 fn fix_program(instructions: &[Instruction]) -> Option<ExecutionResult> {
     (0..instructions.len())
-        .map(|i| permute(&instructions, i))
-        .flatten()
-        .map(|instructions| Program::new(&instructions))
-        .map(|program| program.execute())
-        .find(|result| matches!(result, Finished(_)))
+        .filter_map(|i| permute(instructions, i).map(|instructions| (i, instructions)))
+        .map(|(i, instructions)| (i, Program::new(&instructions).execute()))
+        .find(|(_, result)| matches!(result, Finished(_)))
+        .map(|(i, result)| {
+            trace!(Info, "the program finishes when patching line {}", i + 1);
+            result
+        })
 }
 
 fn permute(instructions: &[Instruction], index: usize) -> Option<Vec<Instruction>> {
@@ -57,58 +116,217 @@ fn permute(instructions: &[Instruction], index: usize) -> Option<Vec<Instruction
 }
 
 #[derive(Clone)]
-enum Instruction {
+pub enum Instruction {
     Accumulator(i32),
     Jump(i32),
     Noop(i32),
 }
 
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Accumulator(i) => write!(f, "acc {:+}", i),
+            Jump(i) => write!(f, "jmp {:+}", i),
+            Noop(i) => write!(f, "nop {:+}", i),
+        }
+    }
+}
+
 #[derive(PartialEq, Debug)]
 enum ExecutionResult {
-    InfiniteLoop(i32),
-    Finished(i32),
+    InfiniteLoop(i64),
+    Finished(i64),
+}
+
+impl ExecutionResult {
+    fn accumulator(&self) -> i64 {
+        match self {
+            InfiniteLoop(accumulator) | Finished(accumulator) => *accumulator,
+        }
+    }
 }
 
-struct Program {
+/// The accumulator and the pointer are wider than the arguments, since each instruction runs at most
+/// once
+pub struct Program {
     instructions: Vec<Instruction>,
+    accumulator: i64,
+    pointer: i64,
+    visited: Vec<bool>,
 }
 
 impl Program {
     fn new(instructions: &[Instruction]) -> Self {
         Self {
             instructions: instructions.to_owned(),
+            accumulator: 0,
+            pointer: 0,
+            visited: vec![false; instructions.len()],
         }
     }
 
-    fn execute(&self) -> ExecutionResult {
-        let mut accumulator: i32 = 0;
-        let mut current_instruction: i32 = 0;
-        let mut visited_instructions: Vec<i32> = Vec::new();
-        while !visited_instructions.contains(&current_instruction)
-            && (current_instruction as usize) < self.instructions.len()
-        {
-            match self.instructions[current_instruction as usize] {
-                Noop(_) => {
-                    visited_instructions.push(current_instruction);
-                    current_instruction += 1;
+    fn execute(mut self) -> ExecutionResult {
+        loop {
+            if let Err(result) = self.step() {
+                if let InfiniteLoop(accumulator) = result {
+                    trace!(
+                        Info,
+                        "loops on line {}: {}, accumulator {}",
+                        self.pointer + 1,
+                        self.instructions[self.pointer as usize],
+                        accumulator
+                    );
                 }
-                Accumulator(i) => {
-                    visited_instructions.push(current_instruction);
-                    current_instruction += 1;
-                    accumulator += i;
-                }
-                Jump(i) => {
-                    visited_instructions.push(current_instruction);
-                    current_instruction += i;
+                break result;
+            }
+        }
+    }
+
+    /// Executes the current instruction, unless the program has finished or is about to loop
+    fn step(&mut self) -> Result<(), ExecutionResult> {
+        let pointer = self.current().ok_or(Finished(self.accumulator))?;
+        if self.visited[pointer] {
+            return Err(InfiniteLoop(self.accumulator));
+        }
+        self.visited[pointer] = true;
+        trace!(
+            Debug,
+            "line {}: {}, accumulator {}",
+            pointer + 1,
+            self.instructions[pointer],
+            self.accumulator
+        );
+        match self.instructions[pointer] {
+            Noop(_) => self.pointer += 1,
+            Accumulator(i) => {
+                self.accumulator += i as i64;
+                self.pointer += 1;
+            }
+            Jump(i) => self.pointer += i as i64,
+        }
+        Ok(())
+    }
+
+    fn current(&self) -> Option<usize> {
+        usize::try_from(self.pointer)
+            .ok()
+            .filter(|pointer| *pointer < self.instructions.len())
+    }
+
+    fn reset(&mut self) {
+        self.accumulator = 0;
+        self.pointer = 0;
+        self.visited = vec![false; self.instructions.len()];
+    }
+
+    fn state(&self) -> String {
+        match self.current() {
+            None => format!("Finished, accumulator {}", self.accumulator),
+            Some(pointer) => format!(
+                "{} line {}: {}, accumulator {}",
+                if self.visited[pointer] {
+                    "Loops at"
+                } else {
+                    "Next"
+                },
+                pointer + 1,
+                self.instructions[pointer],
+                self.accumulator
+            ),
+        }
+    }
+}
+
+impl Session for Program {
+    fn namespace(&self) -> &'static str {
+        "vm"
+    }
+
+    fn commands(&self) -> &'static [&'static str] {
+        &[
+            "run — execute until the program finishes or loops",
+            "step [count] — execute one or count instructions",
+            "reset — go back to the first instruction",
+        ]
+    }
+
+    fn execute(&mut self, command: &str, arguments: &[String]) -> Result<String, ReplError> {
+        match command {
+            "run" => while self.step().is_ok() {},
+            "step" => {
+                let count = if arguments.is_empty() {
+                    1
+                } else {
+                    parse_argument(arguments, 0, "count")?
+                };
+                for _ in 0..count {
+                    if self.step().is_err() {
+                        break;
+                    }
                 }
             }
+            "reset" => self.reset(),
+            _ => return Err(ReplError::UnknownCommand(command.into())),
         }
-        if visited_instructions.contains(&current_instruction) {
-            InfiniteLoop(accumulator)
+        Ok(self.state())
+    }
+}
+
+/// The program runs its first instructions once, then jumps back to the start. Patching that jump
+/// runs the remaining instructions until the end, while patching any other instruction that runs
+/// leads into a `jmp +0` trap: each `jmp` skips a trap that it would run into as a `nop`, and each
+/// `nop` points to a trap.
+fn generate_program(random: &mut Random, size: usize) -> Generated {
+    let size = size.max(4);
+    let looping_size = (size * 3 / 5).max(3);
+    let mut instructions = vec![Jump(2), Jump(0)];
+    let mut traps = vec![1];
+    let mut noops = Vec::new();
+    let mut accumulator = 0;
+    while instructions.len() < looping_size - 1 {
+        match random.below(3) {
+            0 if instructions.len() + 2 < looping_size => {
+                traps.push(instructions.len() + 1);
+                instructions.extend([Jump(2), Jump(0)]);
+            }
+            1 => {
+                noops.push(instructions.len());
+                instructions.push(Noop(0));
+            }
+            _ => {
+                let argument = random_argument(random);
+                accumulator += argument;
+                instructions.push(Accumulator(argument));
+            }
+        }
+    }
+    for noop in noops {
+        instructions[noop] = Noop(*random.choose(&traps) as i32 - noop as i32);
+    }
+    instructions.push(Jump(-(instructions.len() as i32)));
+    let looping_accumulator = accumulator;
+    while instructions.len() < size {
+        let argument = random_argument(random);
+        if random.chance(1, 3) {
+            instructions.push(Noop(argument));
         } else {
-            Finished(accumulator)
+            accumulator += argument;
+            instructions.push(Accumulator(argument));
         }
     }
+    Generated {
+        input: instructions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        part1: Some(looping_accumulator as i128),
+        part2: Some(accumulator as i128),
+    }
+}
+
+fn random_argument(random: &mut Random) -> i32 {
+    random.between(0, 100) as i32 - 50
 }
 
 #[cfg(test)]
@@ -194,20 +412,124 @@ mod program_execution_last_should {
 
     #[test]
     fn return_5_for_example_program() {
-        let program = Program::new(&parse_instructions(vec![
-            "nop +0".into(),
-            "acc +1".into(),
-            "jmp +4".into(),
-            "acc +3".into(),
-            "jmp -3".into(),
-            "acc -99".into(),
-            "acc +1".into(),
-            "jmp -4".into(),
-            "acc +6".into(),
-        ]));
+        let program = Program::new(
+            &parse_instructions(
+                "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6",
+            )
+            .unwrap(),
+        );
 
         let result = program.execute();
 
         assert_eq!(result, InfiniteLoop(5));
     }
 }
+
+#[cfg(test)]
+mod parse_instruction_should {
+    use super::*;
+
+    #[test]
+    fn reject_an_unknown_operation() {
+        assert_eq!(
+            parse_instruction("mul +2").err(),
+            Some(ParseError::new(
+                "mul +2",
+                "unknown operation (acc, jmp or nop)"
+            ))
+        );
+    }
+
+    #[test]
+    fn reject_an_invalid_argument() {
+        assert!(parse_instruction("jmp é").is_err());
+    }
+}
+
+#[cfg(test)]
+mod program_session_should {
+    use super::*;
+    use crate::repl::run_command;
+
+    fn example_program() -> Program {
+        Program::new(&parse_instructions(EXAMPLES[0].input).unwrap())
+    }
+
+    #[test]
+    fn step_through_the_instructions() {
+        let mut program = example_program();
+
+        assert_eq!(
+            run_command(&mut program, "vm step 2"),
+            Ok("Next line 3: jmp +4, accumulator 1".into())
+        );
+    }
+
+    #[test]
+    fn run_until_the_loop() {
+        let mut program = example_program();
+
+        assert_eq!(
+            run_command(&mut program, "vm run"),
+            Ok("Loops at line 2: acc +1, accumulator 5".into())
+        );
+    }
+
+    #[test]
+    fn reset_the_program() {
+        let mut program = example_program();
+        run_command(&mut program, "vm run").unwrap();
+
+        assert_eq!(
+            run_command(&mut program, "vm reset"),
+            Ok("Next line 1: nop +0, accumulator 0".into())
+        );
+    }
+}
+
+#[cfg(test)]
+mod generate_program_should {
+    use super::*;
+
+    #[test]
+    fn finish_with_a_single_patch() {
+        for seed in 0..10 {
+            let generated = generate_program(&mut Random::new(seed), 200);
+            let instructions = parse_instructions(&generated.input).unwrap();
+
+            let finishing = (0..instructions.len())
+                .filter_map(|i| permute(&instructions, i))
+                .filter(|patched| matches!(Program::new(patched).execute(), Finished(_)))
+                .count();
+
+            assert_eq!(instructions.len(), 200);
+            assert_eq!(finishing, 1, "seed {}", seed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod part2_should {
+    use super::*;
+
+    #[test]
+    fn fail_when_no_patch_makes_the_program_finish() {
+        assert_eq!(
+            Day8.part2(&vec![Jump(0), Jump(0), Accumulator(1)]),
+            Err(ParseError::new(
+                "",
+                "no patched instruction makes the program finish"
+            ))
+        );
+    }
+
+    #[test]
+    fn not_overflow_the_accumulator_nor_the_pointer() {
+        let instructions = vec![Accumulator(i32::MAX), Accumulator(i32::MAX), Noop(i32::MAX)];
+
+        assert_eq!(
+            Day8.part2(&instructions),
+            Ok(Answer::from(2 * i32::MAX as i64))
+        );
+    }
+}
@@ -1,32 +1,115 @@
-use crate::puzzle::input::read_lines;
+use crate::error::ParseError;
+use crate::puzzle::input::read_parsed;
+use crate::puzzle::{Answer, Example, Generated, Solution};
+use crate::random::Random;
 use itertools::Itertools;
 
-pub fn execute() {
-    let numbers = get_numbers();
-    let first_invalid_number = validate(&numbers, 25).unwrap();
-    println!("9:1 — First invalid number: {}", first_invalid_number,);
-    let range = find_range_with_sum(&numbers, first_invalid_number);
-    let lowest = range.iter().min().unwrap();
-    let highest = range.iter().max().unwrap();
-    println!(
-        "9:2 — Sum of smallest and largest in range: {}",
-        lowest + highest,
-    );
+/// Each number must be the sum of two of the `preamble_size` numbers before it
+pub struct Day9 {
+    pub preamble_size: usize,
 }
 
-fn get_numbers() -> Vec<u64> {
-    read_lines("day9")
-        .unwrap()
-        .iter()
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse().unwrap())
-        .collect()
+/// The preamble of the personal inputs
+pub const PREAMBLE_SIZE: usize = 25;
+
+impl Day9 {
+    pub const fn new(preamble_size: usize) -> Self {
+        Self { preamble_size }
+    }
+}
+
+impl Solution for Day9 {
+    type Model = Vec<u64>;
+
+    fn day(&self) -> u8 {
+        9
+    }
+
+    fn title(&self) -> &'static str {
+        "Encoding Error"
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        [
+            "First invalid number",
+            "Sum of smallest and largest in range",
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<u64>, ParseError> {
+        get_numbers(input)
+    }
+
+    fn part1(&self, numbers: &Vec<u64>) -> Result<Answer, ParseError> {
+        first_invalid_number(numbers, self.preamble_size).map(Answer::from)
+    }
+
+    fn part2(&self, numbers: &Vec<u64>) -> Result<Answer, ParseError> {
+        let first_invalid_number = first_invalid_number(numbers, self.preamble_size)?;
+        find_range_with_sum(numbers, first_invalid_number)
+            .and_then(|range| range.iter().minmax().into_option())
+            .map(|(lowest, highest)| (lowest + highest).into())
+            .ok_or_else(|| {
+                ParseError::new("", "no range of numbers sums to the first invalid number")
+            })
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> Option<Generated> {
+        // Numbers are sums of the four smallest of their window
+        (self.preamble_size >= 4).then(|| generate_numbers(random, size, self.preamble_size))
+    }
+}
+
+static EXAMPLES: &[Example] = &[Example {
+    input: r"35
+20
+15
+25
+47
+40
+62
+55
+65
+95
+102
+117
+150
+182
+127
+219
+299
+277
+309
+576",
+    part1: Some(127),
+    part2: Some(62),
+    solver: Some(&Day9::new(5)),
+}];
+
+fn get_numbers(input: &str) -> Result<Vec<u64>, ParseError> {
+    read_parsed(input)
+}
+
+fn first_invalid_number(numbers: &[u64], preamble_size: usize) -> Result<u64, ParseError> {
+    validate(numbers, preamble_size).ok_or_else(|| {
+        ParseError::new(
+            "",
+            format!(
+                "every number is the sum of two of the {} numbers before it",
+                preamble_size
+            ),
+        )
+    })
 }
 
 fn validate(numbers: &[u64], preamble_size: usize) -> Option<u64> {
     numbers
         .windows(preamble_size + 1)
-        .find(|window| !is_valid(&window))
+        .find(|window| !is_valid(window))
         .map(|window| window[preamble_size])
 }
 
@@ -35,22 +118,84 @@ fn is_valid(window: &[u64]) -> bool {
     window[..window.len() - 1]
         .iter()
         .combinations(2)
-        .any(|c| c[0] + c[1] == last)
+        .any(|c| c[0].checked_add(*c[1]) == Some(last))
 }
 
-fn find_range_with_sum(numbers: &[u64], sum: u64) -> &[u64] {
-    for i in 0..numbers.len() {
-        let mut s = numbers[i];
-        let mut j = i + 1;
-        while s < sum && j < numbers.len() {
-            s += numbers[j];
-            j += 1;
+/// The first run of at least two numbers with the given sum
+fn find_range_with_sum(numbers: &[u64], sum: u64) -> Option<&[u64]> {
+    (0..numbers.len()).find_map(|start| {
+        let mut total = 0u64;
+        for (end, number) in numbers.iter().enumerate().skip(start) {
+            total = total.checked_add(*number)?;
+            if total == sum && end > start {
+                return Some(&numbers[start..=end]);
+            }
+            if total > sum {
+                return None;
+            }
         }
-        if sum == s {
-            return &numbers[i..j];
+        None
+    })
+}
+
+/// Each number is the sum of two of the four smallest numbers of its window, except the invalid
+/// one, which is the sum of a run of earlier numbers. The smallest number of the window still
+/// doubles with each preamble, so the invalid number comes within the first 40 preambles. The
+/// numbers after it, which neither part reads, are drawn freely up to `size`.
+fn generate_numbers(random: &mut Random, size: usize, preamble_size: usize) -> Generated {
+    let size = size.max(preamble_size + 1);
+    let mut numbers = (0..preamble_size)
+        .map(|_| random.between(1, 50))
+        .collect::<Vec<_>>();
+    let last_invalid_index = size.min(40 * preamble_size) - 1;
+    let invalid_index = random.between(preamble_size as u64, last_invalid_index as u64) as usize;
+    while numbers.len() < size {
+        let mut window = numbers[numbers.len() - preamble_size..].to_vec();
+        if numbers.len() > invalid_index {
+            numbers.push(random.between(1, numbers[invalid_index]));
+        } else if numbers.len() == invalid_index {
+            numbers.push(generate_invalid_number(random, &numbers, &window));
+        } else {
+            window.sort_unstable();
+            let first = random.index(3);
+            let second = random.between(first as u64 + 1, 3) as usize;
+            numbers.push(window[first] + window[second]);
+        }
+    }
+    let invalid_number = numbers[invalid_index];
+    let range = (0..invalid_index)
+        .find_map(|start| {
+            let (mut end, mut sum) = (start, 0);
+            while sum < invalid_number {
+                sum += numbers[end];
+                end += 1;
+            }
+            Some(&numbers[start..end]).filter(|_| sum == invalid_number)
+        })
+        .expect("The invalid number is the sum of a range");
+    Generated {
+        input: numbers
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        part1: Some(invalid_number as i128),
+        part2: Some((range.iter().min().unwrap() + range.iter().max().unwrap()) as i128),
+    }
+}
+
+/// Not the sum of two numbers of the window, nor one of the numbers before it
+fn generate_invalid_number(random: &mut Random, numbers: &[u64], window: &[u64]) -> u64 {
+    loop {
+        let length = random.between(2, numbers.len().min(10) as u64) as usize;
+        let start = random.index(numbers.len() - length + 1);
+        let candidate = numbers[start..start + length].iter().sum();
+        let mut with_candidate = window.to_vec();
+        with_candidate.push(candidate);
+        if !is_valid(&with_candidate) && !numbers.contains(&candidate) {
+            return candidate;
         }
     }
-    &[]
 }
 
 #[cfg(test)]
@@ -95,3 +240,80 @@ mod validate_should {
         assert_eq!(validate(&numbers, 5), Some(127))
     }
 }
+
+#[cfg(test)]
+mod find_range_with_sum_should {
+    use super::*;
+
+    #[test]
+    fn return_the_range_of_the_example() {
+        let numbers = [15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127];
+
+        assert_eq!(find_range_with_sum(&numbers, 127), Some(&numbers[0..4]));
+    }
+
+    #[test]
+    fn ignore_a_single_number() {
+        assert_eq!(find_range_with_sum(&[5, 127, 3], 127), None);
+    }
+
+    #[test]
+    fn not_overflow() {
+        assert_eq!(find_range_with_sum(&[u64::MAX, 1, 2], 3), Some(&[1, 2][..]));
+    }
+}
+
+#[cfg(test)]
+mod part1_should {
+    use super::*;
+
+    #[test]
+    fn fail_when_every_number_is_valid() {
+        let numbers = (1..=25).chain(vec![26, 27]).collect::<Vec<u64>>();
+
+        assert!(Day9::new(PREAMBLE_SIZE).part1(&numbers).is_err());
+        assert!(Day9::new(PREAMBLE_SIZE).part1(&vec![1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn not_overflow_when_adding_two_numbers() {
+        let mut numbers = vec![u64::MAX; 25];
+        numbers.push(1);
+
+        assert_eq!(
+            Day9::new(PREAMBLE_SIZE).part1(&numbers),
+            Ok(Answer::from(1u64))
+        );
+    }
+}
+
+#[cfg(test)]
+mod part2_should {
+    use super::*;
+
+    #[test]
+    fn fail_when_no_range_sums_to_the_invalid_number() {
+        let mut numbers = (1..=25).map(|n| n * 100).collect::<Vec<u64>>();
+        numbers.push(1);
+
+        assert_eq!(
+            Day9::new(PREAMBLE_SIZE).part2(&numbers),
+            Err(ParseError::new(
+                "",
+                "no range of numbers sums to the first invalid number"
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod generate_numbers_should {
+    use super::*;
+
+    #[test]
+    fn produce_as_many_numbers_as_requested_beyond_40_preambles() {
+        let generated = generate_numbers(&mut Random::new(1), 5000, PREAMBLE_SIZE);
+
+        assert_eq!(generated.input.lines().count(), 5000);
+    }
+}
@@ -0,0 +1,51 @@
+macro_rules! embed {
+    ($($name:literal),* $(,)?) => {
+        &[$(($name, include_str!($name))),*]
+    };
+}
+
+static FILES: &[(&str, &str)] = embed!(
+    "2020/day1",
+    "2020/day2",
+    "2020/day3",
+    "2020/day4",
+    "2020/day5",
+    "2020/day6",
+    "2020/day7",
+    "2020/day8",
+    "2020/day9",
+    "2020/day10",
+    "2020/day11",
+    "2020/day12",
+    "2020/day13",
+    "2020/day14",
+    "2020/day15",
+    "2020/day16",
+    "2020/day17",
+    "2020/answers.toml",
+);
+
+pub fn file(name: &str) -> Option<&'static str> {
+    FILES
+        .iter()
+        .find(|(file_name, _)| *file_name == name)
+        .map(|(_, content)| *content)
+}
+
+#[cfg(test)]
+mod file_should {
+    use super::*;
+    use crate::puzzle::PUZZLES;
+
+    #[test]
+    fn contain_the_input_of_each_puzzle() {
+        for puzzle in PUZZLES.iter() {
+            assert!(file(&format!("{}/day{}", puzzle.year(), puzzle.day())).is_some());
+        }
+    }
+
+    #[test]
+    fn not_contain_unknown_files() {
+        assert_eq!(file("mod.rs"), None);
+    }
+}
@@ -1,14 +1,394 @@
-use std::fs::{read_to_string, File};
-use std::io::{BufRead, BufReader, Result};
+use std::env;
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::io::{self, stdin, Read};
+use std::iter::Enumerate;
+use std::path::PathBuf;
+use std::str::{FromStr, Lines};
 
-pub fn read_lines(day: &str) -> Result<Vec<String>> {
-    let file = File::open(format!("src/puzzle/input/{}", day))?;
-    let reader = BufReader::new(file);
+use crate::error::ParseError;
+use crate::puzzle::DEFAULT_YEAR;
 
-    reader.lines().collect()
+#[cfg(feature = "embedded-inputs")]
+mod embedded;
+
+pub const INPUT_DIR_VARIABLE: &str = "AOC_INPUT_DIR";
+const DEFAULT_INPUT_DIR: &str = "src/puzzle/input";
+const STDIN_ARGUMENT: &str = "-";
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+    Embedded(String),
+}
+
+impl InputSource {
+    /// The day file in the directory of its year, or the embedded one when it is not on disk
+    pub fn locate(year: u16, day: u8) -> Self {
+        let name = format!("{}/day{}", year, day);
+        let path = input_file(year, &format!("day{}", day));
+        if !path.exists() && embedded_file(&name).is_some() {
+            InputSource::Embedded(name)
+        } else {
+            InputSource::File(path)
+        }
+    }
+
+    pub fn resolve(year: u16, day: u8, input_override: Option<&InputSource>) -> Self {
+        input_override
+            .cloned()
+            .unwrap_or_else(|| InputSource::locate(year, day))
+    }
+
+    pub fn read(&self) -> io::Result<String> {
+        match self {
+            InputSource::File(path) => read_to_string(path),
+            InputSource::Stdin => {
+                let mut input = String::new();
+                stdin().read_to_string(&mut input)?;
+                Ok(input)
+            }
+            InputSource::Embedded(name) => embedded_file(name)
+                .map(String::from)
+                .ok_or_else(|| io::ErrorKind::NotFound.into()),
+        }
+    }
+}
+
+impl From<&str> for InputSource {
+    fn from(argument: &str) -> Self {
+        if argument == STDIN_ARGUMENT {
+            InputSource::Stdin
+        } else {
+            InputSource::File(argument.into())
+        }
+    }
+}
+
+impl Display for InputSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputSource::File(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "standard input"),
+            InputSource::Embedded(name) => write!(f, "embedded {}", name),
+        }
+    }
+}
+
+pub fn input_directory() -> PathBuf {
+    env::var_os(INPUT_DIR_VARIABLE)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_INPUT_DIR))
+}
+
+/// A file of the directory of the year, or of the input directory itself for the default year
+/// as it was laid out before years
+pub fn input_file(year: u16, name: &str) -> PathBuf {
+    let path = input_directory().join(year.to_string()).join(name);
+    let legacy_path = input_directory().join(name);
+    if !path.exists() && year == DEFAULT_YEAR && legacy_path.exists() {
+        legacy_path
+    } else {
+        path
+    }
+}
+
+/// A file of the input directory baked into the binary with the `embedded-inputs` feature
+#[cfg(feature = "embedded-inputs")]
+pub fn embedded_file(name: &str) -> Option<&'static str> {
+    embedded::file(name)
+}
+
+#[cfg(not(feature = "embedded-inputs"))]
+pub fn embedded_file(_name: &str) -> Option<&'static str> {
+    None
+}
+
+pub fn lines(input: &str) -> Vec<String> {
+    input.lines().map(String::from).collect()
+}
+
+/// Parses each non-empty line, locating errors by their line number (starting at 1)
+pub fn parse_lines<'a, T>(
+    input: &'a str,
+    mut parse: impl FnMut(&'a str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| parse(line).map_err(|error| error.at_line(i + 1)))
+        .collect()
+}
+
+pub fn parse_number<T: FromStr>(text: &str) -> Result<T, ParseError> {
+    text.trim()
+        .parse()
+        .map_err(|_| ParseError::new(text, "invalid number"))
+}
+
+/// Parses each non-empty line as a `T`
+pub fn read_parsed<T: FromStr>(input: &str) -> Result<Vec<T>, ParseError> {
+    parse_lines(input, parse_number)
+}
+
+/// Parses comma-separated values, one list after the other when there are several lines
+pub fn parse_list<T: FromStr>(input: &str) -> Result<Vec<T>, ParseError> {
+    parse_lines(input, |line| line.split(',').map(parse_number).collect())
+        .map(|lists: Vec<Vec<T>>| lists.into_iter().flatten().collect())
+}
+
+/// Consecutive non-blank lines
+#[derive(Clone, PartialEq, Debug)]
+pub struct Group<'a> {
+    /// The number of the first line in the input (starting at 1)
+    pub first_line: usize,
+    pub lines: Vec<&'a str>,
+}
+
+impl<'a> Group<'a> {
+    pub fn numbered_lines(&self) -> impl Iterator<Item = (usize, &'a str)> + '_ {
+        (self.first_line..).zip(self.lines.iter().copied())
+    }
+
+    /// The lines following the first one, such as those under a `header:`
+    pub fn body(&self) -> Group<'a> {
+        Group {
+            first_line: self.first_line + 1,
+            lines: self.lines.iter().skip(1).copied().collect(),
+        }
+    }
+
+    /// Parses each line, locating errors by their line number in the input
+    pub fn parse_lines<T>(
+        &self,
+        parse: impl Fn(&'a str) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        self.numbered_lines()
+            .map(|(n, line)| parse(line).map_err(|error| error.at_line(n)))
+            .collect()
+    }
+}
+
+pub struct Groups<'a> {
+    lines: Enumerate<Lines<'a>>,
+}
+
+impl<'a> Iterator for Groups<'a> {
+    type Item = Group<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (i, first) = self.lines.find(|(_, line)| !line.trim().is_empty())?;
+        let mut lines = vec![first];
+        lines.extend(
+            self.lines
+                .by_ref()
+                .map(|(_, line)| line)
+                .take_while(|line| !line.trim().is_empty()),
+        );
+        Some(Group {
+            first_line: i + 1,
+            lines,
+        })
+    }
+}
+
+/// Splits the input on blank lines, whether lines end with `\n` or `\r\n`
+pub fn groups(input: &str) -> Groups<'_> {
+    Groups {
+        lines: input.lines().enumerate(),
+    }
+}
+
+/// Parses each group, locating errors without a line on the first line of their group
+pub fn parse_groups<'a, T>(
+    input: &'a str,
+    parse: impl Fn(&Group<'a>) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    groups(input)
+        .map(|group| {
+            parse(&group).map_err(|error| match error.line {
+                Some(_) => error,
+                None => error.at_line(group.first_line),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod parse_lines_should {
+    use super::*;
+
+    #[test]
+    fn skip_empty_lines() {
+        assert_eq!(parse_lines("1\n\n2\n", parse_number), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn locate_an_error_on_its_line() {
+        assert_eq!(
+            parse_lines::<u32>("1\n\nabc\n", parse_number),
+            Err(ParseError::new("abc", "invalid number").at_line(3))
+        );
+    }
 }
 
-pub fn read_input(day: &str) -> String {
-    read_to_string(format!("src/puzzle/input/{}", day))
-        .unwrap_or_else(|_| panic!("Could not read input file for {}", day))
+#[cfg(test)]
+mod read_parsed_should {
+    use super::*;
+
+    #[test]
+    fn parse_each_line() {
+        assert_eq!(read_parsed::<i64>("1\r\n-2\r\n"), Ok(vec![1, -2]));
+    }
+
+    #[test]
+    fn locate_an_error_on_its_line() {
+        assert_eq!(
+            read_parsed::<u8>("1\n256\n"),
+            Err(ParseError::new("256", "invalid number").at_line(2))
+        );
+    }
+}
+
+#[cfg(test)]
+mod parse_list_should {
+    use super::*;
+
+    #[test]
+    fn parse_comma_separated_values() {
+        assert_eq!(parse_list("0, 3,6\n"), Ok(vec![0, 3, 6]));
+    }
+
+    #[test]
+    fn locate_an_error_on_its_line() {
+        assert_eq!(
+            parse_list::<u32>("7,1\n14,x\n"),
+            Err(ParseError::new("x", "invalid number").at_line(2))
+        );
+    }
+}
+
+#[cfg(test)]
+mod groups_should {
+    use super::*;
+
+    #[test]
+    fn split_on_blank_lines() {
+        assert_eq!(
+            groups("a\nb\n\nc\n").collect::<Vec<_>>(),
+            vec![
+                Group {
+                    first_line: 1,
+                    lines: vec!["a", "b"]
+                },
+                Group {
+                    first_line: 4,
+                    lines: vec!["c"]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn handle_crlf_and_several_blank_lines() {
+        assert_eq!(
+            groups("\r\na\r\n\r\n  \r\nb\r\n").collect::<Vec<_>>(),
+            vec![
+                Group {
+                    first_line: 2,
+                    lines: vec!["a"]
+                },
+                Group {
+                    first_line: 5,
+                    lines: vec!["b"]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn return_the_body_under_a_header() {
+        let group = groups("your ticket:\n7,1,14").next().unwrap();
+
+        assert_eq!(
+            group.body().numbered_lines().collect::<Vec<_>>(),
+            vec![(2, "7,1,14")]
+        );
+    }
+}
+
+#[cfg(test)]
+mod parse_groups_should {
+    use super::*;
+
+    #[test]
+    fn locate_an_error_in_a_group_on_its_line_in_the_input() {
+        let result = parse_groups("a\n\nb\nc", |group| {
+            group.parse_lines(|line| match line {
+                "c" => Err(ParseError::new(line, "unexpected line")),
+                _ => Ok(line),
+            })
+        });
+
+        assert_eq!(
+            result,
+            Err(ParseError::new("c", "unexpected line").at_line(4))
+        );
+    }
+
+    #[test]
+    fn locate_an_error_of_a_whole_group_on_its_first_line() {
+        let result: Result<Vec<()>, _> =
+            parse_groups("a\n\nb", |_| Err(ParseError::new("", "invalid group")));
+
+        assert_eq!(result, Err(ParseError::new("", "invalid group").at_line(1)));
+    }
+}
+
+#[cfg(test)]
+mod input_source_should {
+    use super::*;
+
+    #[test]
+    fn be_stdin_when_argument_is_a_dash() {
+        assert_eq!(InputSource::from("-"), InputSource::Stdin);
+    }
+
+    #[test]
+    fn be_a_file_when_argument_is_a_path() {
+        assert_eq!(
+            InputSource::from("inputs/day14"),
+            InputSource::File("inputs/day14".into())
+        );
+    }
+
+    #[test]
+    fn resolve_to_the_override_when_there_is_one() {
+        let input_override = InputSource::File("my_input".into());
+
+        assert_eq!(
+            InputSource::resolve(2020, 3, Some(&input_override)),
+            input_override
+        );
+    }
+
+    #[test]
+    fn resolve_to_the_day_file_when_there_is_no_override() {
+        match InputSource::resolve(2020, 3, None) {
+            InputSource::File(path) => assert!(path.ends_with("2020/day3")),
+            source => panic!("Expected a file, got {}", source),
+        }
+    }
+
+    #[test]
+    fn read_an_embedded_file() {
+        let source = InputSource::Embedded("2020/day15".into());
+
+        match embedded_file("2020/day15") {
+            Some(content) => assert_eq!(source.read().unwrap(), content),
+            None => assert!(source.read().is_err()),
+        }
+    }
 }
@@ -1,3 +1,12 @@
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::error::{Error, ParseError};
+use crate::random::Random;
+use crate::repl::Session;
+
 pub mod day1;
 pub mod day10;
 pub mod day11;
@@ -16,3 +25,530 @@ pub mod day7;
 pub mod day8;
 pub mod day9;
 pub mod input;
+
+pub static PUZZLES: [&dyn Puzzle; 17] = [
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+    &day6::Day6,
+    &day7::Day7,
+    &day8::Day8,
+    &day9::Day9::new(day9::PREAMBLE_SIZE),
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+];
+
+pub const DEFAULT_YEAR: u16 = 2020;
+
+pub fn find_puzzle(year: u16, day: u8) -> Option<&'static dyn Puzzle> {
+    puzzles_of(year).find(|puzzle| puzzle.day() == day)
+}
+
+pub fn puzzles_of(year: u16) -> impl Iterator<Item = &'static dyn Puzzle> {
+    PUZZLES
+        .iter()
+        .copied()
+        .filter(move |puzzle| puzzle.year() == year)
+}
+
+/// The years with at least one puzzle, in ascending order
+pub fn years() -> Vec<u16> {
+    let mut years = PUZZLES
+        .iter()
+        .map(|puzzle| puzzle.year())
+        .collect::<Vec<_>>();
+    years.dedup();
+    years
+}
+
+pub fn solve(year: u16, day: u8, part: Part, input: &str) -> Result<Answer, Error> {
+    let puzzle = find_puzzle(year, day).ok_or(Error::UnknownDay { year, day })?;
+    let execution = puzzle.execute(input, &[part])?;
+    Ok(execution
+        .results
+        .into_iter()
+        .map(|result| result.answer)
+        .next()
+        .expect("The requested part is always solved"))
+}
+
+pub trait Puzzle: Sync {
+    fn year(&self) -> u16;
+
+    fn day(&self) -> u8;
+
+    fn title(&self) -> &'static str;
+
+    fn labels(&self) -> [&'static str; 2];
+
+    fn examples(&self) -> &'static [Example];
+
+    fn execute(&self, input: &str, parts: &[Part]) -> Result<Execution, Error>;
+
+    /// Parses the input into a session when the day supports exploring its model
+    fn session(&self, input: &str) -> Result<Option<Box<dyn Session>>, Error>;
+
+    /// Executes the parts, then pictures the model they solved
+    fn execute_with_visuals(
+        &self,
+        input: &str,
+        parts: &[Part],
+    ) -> Result<(Execution, Vec<Visual>), Error>;
+
+    /// The same seed and size always give the same input
+    fn generate(&self, seed: u64, size: usize) -> Option<Generated>;
+
+    fn label(&self, part: Part) -> &'static str {
+        match part {
+            Part::One => self.labels()[0],
+            Part::Two => self.labels()[1],
+        }
+    }
+}
+
+pub trait Solution: Sync {
+    type Model;
+
+    /// Days of another year override it
+    fn year(&self) -> u16 {
+        DEFAULT_YEAR
+    }
+
+    fn day(&self) -> u8;
+
+    fn title(&self) -> &'static str;
+
+    fn labels(&self) -> [&'static str; 2];
+
+    /// The samples of the puzzle statement
+    fn examples(&self) -> &'static [Example] {
+        &[]
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Model, ParseError>;
+
+    /// Fails when the parsed input has no answer
+    fn part1(&self, model: &Self::Model) -> Result<Answer, ParseError>;
+
+    fn part2(&self, model: &Self::Model) -> Result<Answer, ParseError>;
+
+    fn session(&self, _model: Self::Model) -> Option<Box<dyn Session>> {
+        None
+    }
+
+    /// Pictures of the model worth showing in a report, drawn after the parts solved it
+    fn visuals(&self, _model: &Self::Model) -> Vec<Visual> {
+        Vec::new()
+    }
+
+    /// A random valid input of about `size` lines, records when they span several lines, or cells
+    /// for a square
+    fn generate(&self, _random: &mut Random, _size: usize) -> Option<Generated> {
+        None
+    }
+}
+
+impl<S: Solution> Puzzle for S {
+    fn year(&self) -> u16 {
+        Solution::year(self)
+    }
+
+    fn day(&self) -> u8 {
+        Solution::day(self)
+    }
+
+    fn title(&self) -> &'static str {
+        Solution::title(self)
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        Solution::labels(self)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        Solution::examples(self)
+    }
+
+    fn execute(&self, input: &str, parts: &[Part]) -> Result<Execution, Error> {
+        solve_parts(self, input, parts).map(|(_, execution)| execution)
+    }
+
+    fn execute_with_visuals(
+        &self,
+        input: &str,
+        parts: &[Part],
+    ) -> Result<(Execution, Vec<Visual>), Error> {
+        let (model, execution) = solve_parts(self, input, parts)?;
+        Ok((execution, Solution::visuals(self, &model)))
+    }
+
+    fn session(&self, input: &str) -> Result<Option<Box<dyn Session>>, Error> {
+        let model = self
+            .parse(input)
+            .map_err(|error| error.in_day(Solution::day(self)))?;
+        Ok(Solution::session(self, model))
+    }
+
+    fn generate(&self, seed: u64, size: usize) -> Option<Generated> {
+        Solution::generate(self, &mut Random::new(seed), size)
+    }
+}
+
+/// The model is kept for what comes after the parts
+fn solve_parts<S: Solution>(
+    solution: &S,
+    input: &str,
+    parts: &[Part],
+) -> Result<(S::Model, Execution), Error> {
+    let (model, parse_duration) = timed(|| solution.parse(input));
+    let model = model.map_err(|error| error.in_day(solution.day()))?;
+    let results = parts
+        .iter()
+        .map(|part| {
+            let (answer, duration) = timed(|| match part {
+                Part::One => solution.part1(&model),
+                Part::Two => solution.part2(&model),
+            });
+            Ok(PartResult {
+                part: *part,
+                answer: answer.map_err(|error| error.in_day(solution.day()))?,
+                duration,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+    Ok((
+        model,
+        Execution {
+            parse_duration,
+            results,
+        },
+    ))
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+pub struct Example {
+    pub input: &'static str,
+    pub part1: Option<i128>,
+    pub part2: Option<i128>,
+    /// Solves the example instead of the registered day, when the statement changes a parameter
+    /// for its sample
+    pub solver: Option<&'static dyn Puzzle>,
+}
+
+impl Example {
+    pub fn solver(&self, puzzle: &'static dyn Puzzle) -> &'static dyn Puzzle {
+        self.solver.unwrap_or(puzzle)
+    }
+
+    pub fn expected(&self, part: Part) -> Option<Answer> {
+        match part {
+            Part::One => self.part1,
+            Part::Two => self.part2,
+        }
+        .map(Answer::Integer)
+    }
+}
+
+/// An input of a generator, with the answers that its construction guarantees
+pub struct Generated {
+    pub input: String,
+    pub part1: Option<i128>,
+    pub part2: Option<i128>,
+}
+
+impl Generated {
+    pub fn expected(&self, part: Part) -> Option<Answer> {
+        match part {
+            Part::One => self.part1,
+            Part::Two => self.part2,
+        }
+        .map(Answer::Integer)
+    }
+}
+
+pub struct Visual {
+    pub title: &'static str,
+    /// Plain text, one row per line
+    pub picture: String,
+}
+
+pub struct Execution {
+    pub parse_duration: Duration,
+    pub results: Vec<PartResult>,
+}
+
+impl Execution {
+    pub fn result(&self, part: Part) -> Option<&PartResult> {
+        self.results.iter().find(|result| result.part == part)
+    }
+
+    pub fn answer(&self, part: Part) -> Option<&Answer> {
+        self.result(part).map(|result| &result.answer)
+    }
+
+    /// Parse and parts together
+    pub fn duration(&self) -> Duration {
+        self.parse_duration
+            + self
+                .results
+                .iter()
+                .map(|result| result.duration)
+                .sum::<Duration>()
+    }
+}
+
+pub struct PartResult {
+    pub part: Part,
+    pub answer: Answer,
+    pub duration: Duration,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(&self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+
+    pub fn from_number(number: u8) -> Option<Self> {
+        match number {
+            1 => Some(Part::One),
+            2 => Some(Part::Two),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize)]
+#[serde(untagged)]
+pub enum Answer {
+    Integer(i128),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Integer(value) => write!(f, "{}", value),
+            Answer::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+macro_rules! integer_answer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer::Integer(value as i128)
+                }
+            }
+        )*
+    };
+}
+
+integer_answer!(u16, u32, u64, usize, i32, i64, i128, isize);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+#[cfg(test)]
+mod registry_should {
+    use super::*;
+
+    #[test]
+    fn contain_each_day_of_2020_once_in_order() {
+        let days = puzzles_of(2020).map(|p| p.day()).collect::<Vec<_>>();
+
+        assert_eq!(days, (1..=17).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn be_sorted_by_year_then_day() {
+        let keys = PUZZLES
+            .iter()
+            .map(|p| (p.year(), p.day()))
+            .collect::<Vec<_>>();
+
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn find_a_puzzle_by_year_and_day() {
+        assert_eq!(
+            find_puzzle(2020, 14).map(|p| p.title()),
+            Some("Docking Data")
+        );
+    }
+
+    #[test]
+    fn not_find_an_unknown_day() {
+        assert!(find_puzzle(2020, 25).is_none());
+    }
+
+    #[test]
+    fn not_find_a_day_of_another_year() {
+        assert!(find_puzzle(2019, 14).is_none());
+    }
+
+    #[test]
+    fn list_each_year_once() {
+        assert_eq!(years(), vec![2020]);
+    }
+}
+
+#[cfg(test)]
+mod solve_should {
+    use super::*;
+
+    #[test]
+    fn return_the_answer_of_a_part() {
+        assert_eq!(
+            solve(2020, 15, Part::One, "0,3,6"),
+            Ok(Answer::Integer(436))
+        );
+    }
+
+    #[test]
+    fn fail_on_an_unknown_day() {
+        assert_eq!(
+            solve(2020, 25, Part::One, ""),
+            Err(Error::UnknownDay {
+                year: 2020,
+                day: 25
+            })
+        );
+    }
+
+    #[test]
+    fn fail_on_an_invalid_input() {
+        assert_eq!(
+            solve(2020, 1, Part::One, "1721\nabc\n"),
+            Err(ParseError::new("abc", "invalid number")
+                .at_line(2)
+                .in_day(1))
+        );
+    }
+
+    #[test]
+    fn fail_on_an_input_without_answer() {
+        assert_eq!(
+            solve(
+                2020,
+                5,
+                Part::Two,
+                "FBFBBFFRLR
+FBFBBFFRRL"
+            ),
+            Err(ParseError::new("", "no free seat between two boarding passes").in_day(5))
+        );
+    }
+}
+
+#[cfg(test)]
+mod examples_should {
+    use super::*;
+
+    #[test]
+    fn produce_their_expected_answers() {
+        for puzzle in PUZZLES.iter() {
+            for example in puzzle.examples() {
+                let parts = Part::ALL
+                    .iter()
+                    .copied()
+                    .filter(|part| example.expected(*part).is_some())
+                    // The 30 millionth round is too slow without optimizations
+                    .filter(|part| (puzzle.day(), *part) != (15, Part::Two))
+                    .collect::<Vec<_>>();
+                let execution = example
+                    .solver(*puzzle)
+                    .execute(example.input, &parts)
+                    .unwrap();
+                for part in parts {
+                    assert_eq!(
+                        execution.answer(part),
+                        example.expected(part).as_ref(),
+                        "day {} part {}",
+                        puzzle.day(),
+                        part
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod generators_should {
+    use super::*;
+
+    #[test]
+    fn produce_valid_inputs_with_their_expected_answers() {
+        for puzzle in PUZZLES.iter() {
+            for seed in 0..5 {
+                let generated = match puzzle.generate(seed, 60) {
+                    Some(generated) => generated,
+                    None => continue,
+                };
+                let parts = Part::ALL
+                    .iter()
+                    .copied()
+                    .filter(|part| generated.expected(*part).is_some())
+                    .collect::<Vec<_>>();
+                let execution = puzzle
+                    .execute(&generated.input, &parts)
+                    .unwrap_or_else(|error| panic!("seed {}: {}", seed, error));
+                for part in parts {
+                    assert_eq!(
+                        execution.answer(part),
+                        generated.expected(part).as_ref(),
+                        "day {} part {} seed {}",
+                        puzzle.day(),
+                        part,
+                        seed
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn repeat_the_same_input_for_the_same_seed() {
+        for puzzle in PUZZLES.iter() {
+            let input = |seed| puzzle.generate(seed, 20).map(|generated| generated.input);
+
+            assert_eq!(input(3), input(3), "day {}", puzzle.day());
+        }
+    }
+}
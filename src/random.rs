@@ -0,0 +1,94 @@
+//! A small seeded generator (splitmix64), so that a seed gives the same generated inputs on
+//! every platform and release.
+
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `[0, bound)`; the bound must be positive
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "The bound must be positive");
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// A value between `low` and `high`, both included
+    pub fn between(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low <= high, "The range must not be empty");
+        match (high - low).checked_add(1) {
+            Some(bound) => low + self.below(bound),
+            None => self.next_u64(),
+        }
+    }
+
+    /// An index in `[0, len)`
+    pub fn index(&mut self, len: usize) -> usize {
+        self.below(len as u64) as usize
+    }
+
+    /// True `numerator` times out of `denominator`
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.below(denominator) < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod random_should {
+    use super::*;
+
+    #[test]
+    fn repeat_the_same_values_for_the_same_seed() {
+        let values = |seed| {
+            let mut random = Random::new(seed);
+            (0..5).map(|_| random.next_u64()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(values(42), values(42));
+        assert_ne!(values(42), values(43));
+    }
+
+    #[test]
+    fn stay_within_the_bounds() {
+        let mut random = Random::new(7);
+
+        for _ in 0..1000 {
+            assert!((3..=5).contains(&random.between(3, 5)));
+            assert!(random.below(10) < 10);
+        }
+        assert_eq!(random.between(8, 8), 8);
+    }
+
+    #[test]
+    fn shuffle_without_losing_items() {
+        let mut random = Random::new(1);
+        let mut items = (0..20).collect::<Vec<_>>();
+
+        random.shuffle(&mut items);
+
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(PartialEq, Debug)]
+pub enum ReplError {
+    UnknownCommand(String),
+    MissingArgument(&'static str),
+    InvalidArgument(String),
+    UnterminatedQuote,
+}
+
+impl Display for ReplError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplError::UnknownCommand(command) => {
+                write!(f, "Unknown command (try help): {}", command)
+            }
+            ReplError::MissingArgument(argument) => write!(f, "Missing argument: {}", argument),
+            ReplError::InvalidArgument(argument) => write!(f, "Invalid argument: {}", argument),
+            ReplError::UnterminatedQuote => write!(f, "Unterminated quote"),
+        }
+    }
+}
+
+/// A parsed model that answers commands such as `bags contained-by "shiny gold"`
+pub trait Session {
+    /// The first word of every command
+    fn namespace(&self) -> &'static str;
+
+    /// One line per command, without the namespace
+    fn commands(&self) -> &'static [&'static str];
+
+    fn execute(&mut self, command: &str, arguments: &[String]) -> Result<String, ReplError>;
+}
+
+pub fn run_command(session: &mut dyn Session, line: &str) -> Result<String, ReplError> {
+    let words = split_words(line)?;
+    match words.split_first() {
+        Some((namespace, words)) if namespace == session.namespace() => match words.split_first() {
+            Some((command, arguments)) => session.execute(command, arguments),
+            None => Err(ReplError::MissingArgument("command")),
+        },
+        Some((word, _)) => Err(ReplError::UnknownCommand(word.clone())),
+        None => Ok(String::new()),
+    }
+}
+
+pub fn help(session: &dyn Session) -> String {
+    session
+        .commands()
+        .iter()
+        .map(|command| format!("{} {}", session.namespace(), command))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits on whitespace, keeping double-quoted words together
+fn split_words(line: &str) -> Result<Vec<String>, ReplError> {
+    let mut words = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => continue,
+            '"' => {
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(ReplError::UnterminatedQuote),
+                    }
+                }
+                words.push(word);
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    word.push(c);
+                }
+                words.push(word);
+            }
+        }
+    }
+    Ok(words)
+}
+
+pub fn parse_argument<T: std::str::FromStr>(
+    arguments: &[String],
+    index: usize,
+    name: &'static str,
+) -> Result<T, ReplError> {
+    let argument = arguments
+        .get(index)
+        .ok_or(ReplError::MissingArgument(name))?;
+    argument
+        .parse()
+        .map_err(|_| ReplError::InvalidArgument(argument.clone()))
+}
+
+#[cfg(test)]
+mod split_words_should {
+    use super::*;
+
+    #[test]
+    fn keep_quoted_words_together() {
+        assert_eq!(
+            split_words(r#" bags  contained-by "shiny gold" "#),
+            Ok(vec![
+                "bags".to_string(),
+                "contained-by".to_string(),
+                "shiny gold".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn fail_on_an_unterminated_quote() {
+        assert_eq!(
+            split_words(r#"bags contained-by "shiny"#),
+            Err(ReplError::UnterminatedQuote)
+        );
+    }
+}
+
+#[cfg(test)]
+mod run_command_should {
+    use super::*;
+
+    struct Echo;
+
+    impl Session for Echo {
+        fn namespace(&self) -> &'static str {
+            "echo"
+        }
+
+        fn commands(&self) -> &'static [&'static str] {
+            &["say <word>"]
+        }
+
+        fn execute(&mut self, command: &str, arguments: &[String]) -> Result<String, ReplError> {
+            match command {
+                "say" => Ok(arguments.join(" ")),
+                _ => Err(ReplError::UnknownCommand(command.into())),
+            }
+        }
+    }
+
+    #[test]
+    fn dispatch_a_command_of_the_namespace() {
+        assert_eq!(run_command(&mut Echo, "echo say hello"), Ok("hello".into()));
+    }
+
+    #[test]
+    fn reject_another_namespace() {
+        assert_eq!(
+            run_command(&mut Echo, "vm run"),
+            Err(ReplError::UnknownCommand("vm".into()))
+        );
+    }
+
+    #[test]
+    fn require_a_command() {
+        assert_eq!(
+            run_command(&mut Echo, "echo"),
+            Err(ReplError::MissingArgument("command"))
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::time::Duration;
+
+use itertools::Itertools;
+
+use crate::timing::format_duration;
+use advent2020::puzzle::{Execution, Puzzle, Visual};
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em auto; max-width: 60em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; }
+td.number { text-align: right; }
+pre { background: #f6f6f6; padding: 1em; line-height: 1; }";
+
+pub struct DayReport {
+    pub puzzle: &'static dyn Puzzle,
+    pub execution: Execution,
+    pub visuals: Vec<Visual>,
+}
+
+struct Row {
+    day: String,
+    part: String,
+    description: &'static str,
+    answer: String,
+    time: String,
+}
+
+fn title(days: &[DayReport]) -> String {
+    format!(
+        "Advent of Code {}",
+        days.iter()
+            .map(|day| day.puzzle.year())
+            .sorted()
+            .dedup()
+            .join(", ")
+    )
+}
+
+fn rows(days: &[DayReport]) -> Vec<Row> {
+    days.iter()
+        .flat_map(|day| {
+            day.execution.results.iter().map(move |result| Row {
+                day: day.puzzle.day().to_string(),
+                part: result.part.to_string(),
+                description: day.puzzle.label(result.part),
+                answer: result.answer.to_string(),
+                time: format_duration(result.duration),
+            })
+        })
+        .collect()
+}
+
+fn total_time(days: &[DayReport]) -> String {
+    format_duration(
+        days.iter()
+            .map(|day| day.execution.duration())
+            .sum::<Duration>(),
+    )
+}
+
+pub fn to_markdown(days: &[DayReport]) -> String {
+    let mut report = format!("# {}\n\n", title(days));
+    report.push_str("| Day | Part | Description | Answer | Time |\n");
+    report.push_str("|----:|-----:|:------------|-------:|-----:|\n");
+    for row in rows(days) {
+        report.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            row.day,
+            row.part,
+            escape_cell(row.description),
+            escape_cell(&row.answer),
+            row.time
+        ));
+    }
+    report.push_str(&format!(
+        "\nTotal time, parsing included: {}\n",
+        total_time(days)
+    ));
+    for day in days.iter().filter(|day| !day.visuals.is_empty()) {
+        report.push_str(&format!(
+            "\n## Day {} — {}\n",
+            day.puzzle.day(),
+            day.puzzle.title()
+        ));
+        for visual in &day.visuals {
+            report.push_str(&format!(
+                "\n### {}\n\n```text\n{}\n```\n",
+                visual.title,
+                visual.picture.trim_end()
+            ));
+        }
+    }
+    report
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// A single page with its style inlined
+pub fn to_html(days: &[DayReport]) -> String {
+    let mut report = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>\n{style}\n</style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+        title = title(days),
+        style = STYLE
+    );
+    report.push_str(
+        "<table>\n<tr><th>Day</th><th>Part</th><th>Description</th><th>Answer</th><th>Time</th></tr>\n",
+    );
+    for row in rows(days) {
+        report.push_str(&format!(
+            "<tr><td class=\"number\">{}</td><td class=\"number\">{}</td><td>{}</td>\
+             <td class=\"number\">{}</td><td class=\"number\">{}</td></tr>\n",
+            row.day,
+            row.part,
+            escape_html(row.description),
+            escape_html(&row.answer),
+            row.time
+        ));
+    }
+    report.push_str(&format!(
+        "</table>\n<p>Total time, parsing included: {}</p>\n",
+        total_time(days)
+    ));
+    for day in days.iter().filter(|day| !day.visuals.is_empty()) {
+        report.push_str(&format!(
+            "<h2>Day {} — {}</h2>\n",
+            day.puzzle.day(),
+            escape_html(day.puzzle.title())
+        ));
+        for visual in &day.visuals {
+            report.push_str(&format!(
+                "<h3>{}</h3>\n<pre>{}</pre>\n",
+                escape_html(visual.title),
+                escape_html(visual.picture.trim_end())
+            ));
+        }
+    }
+    report.push_str("</body>\n</html>\n");
+    report
+}
+
+fn escape_html(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod report_should {
+    use super::*;
+    use advent2020::puzzle::{find_puzzle, Answer, Part, PartResult};
+
+    fn day_11_report() -> DayReport {
+        DayReport {
+            puzzle: find_puzzle(2020, 11).unwrap(),
+            execution: Execution {
+                parse_duration: Duration::from_micros(8),
+                results: vec![PartResult {
+                    part: Part::One,
+                    answer: Answer::Integer(2441),
+                    duration: Duration::from_micros(12),
+                }],
+            },
+            visuals: vec![Visual {
+                title: "Stable area",
+                picture: "#.#\n".into(),
+            }],
+        }
+    }
+
+    #[test]
+    fn write_a_markdown_table_and_the_visuals() {
+        assert_eq!(
+            to_markdown(&[day_11_report()]),
+            "# Advent of Code 2020\n\
+             \n\
+             | Day | Part | Description | Answer | Time |\n\
+             |----:|-----:|:------------|-------:|-----:|\n\
+             | 11 | 1 | Number of occupied seats after stabilization | 2441 | 12.0 µs |\n\
+             \n\
+             Total time, parsing included: 20.0 µs\n\
+             \n\
+             ## Day 11 — Seating System\n\
+             \n\
+             ### Stable area\n\
+             \n\
+             ```text\n\
+             #.#\n\
+             ```\n"
+        );
+    }
+
+    #[test]
+    fn escape_pipes_in_markdown_cells() {
+        assert_eq!(escape_cell("a|b"), "a\\|b");
+    }
+
+    #[test]
+    fn write_the_visuals_as_escaped_html() {
+        let mut report = day_11_report();
+        report.visuals[0].picture = "<#>\n".into();
+
+        assert!(to_html(&[report]).contains("<h3>Stable area</h3>\n<pre>&lt;#&gt;</pre>\n"));
+    }
+}
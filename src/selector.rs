@@ -0,0 +1,224 @@
+use std::fmt::{Display, Formatter};
+
+use crate::puzzle::{find_puzzle, puzzles_of, Part, Puzzle};
+
+#[derive(PartialEq, Debug)]
+pub enum SelectorError {
+    InvalidDay(String),
+    InvalidPart(String),
+    InvalidRange(String),
+    NoMatchingDay(u16, String),
+}
+
+impl Display for SelectorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelectorError::InvalidDay(day) => write!(f, "Invalid day: {}", day),
+            SelectorError::InvalidPart(part) => write!(f, "Invalid part (1 or 2): {}", part),
+            SelectorError::InvalidRange(range) => write!(f, "Invalid range of days: {}", range),
+            SelectorError::NoMatchingDay(year, selector) => {
+                write!(
+                    f,
+                    "No puzzle of {} matches the selector: {}",
+                    year, selector
+                )
+            }
+        }
+    }
+}
+
+pub struct Selection {
+    pub puzzle: &'static dyn Puzzle,
+    pub parts: Vec<Part>,
+}
+
+pub fn select_puzzles(year: u16, selector: &str) -> Result<Vec<Selection>, SelectorError> {
+    let selections = parse_selector(year, selector)?
+        .into_iter()
+        .flat_map(|(day, parts)| find_puzzle(year, day).map(|puzzle| Selection { puzzle, parts }))
+        .collect::<Vec<_>>();
+    if selections.is_empty() {
+        Err(SelectorError::NoMatchingDay(year, selector.into()))
+    } else {
+        Ok(selections)
+    }
+}
+
+/// Days come in ascending order, whatever their order in the selector
+fn parse_selector(year: u16, selector: &str) -> Result<Vec<(u8, Vec<Part>)>, SelectorError> {
+    let mut days: Vec<(u8, Vec<Part>)> = Vec::new();
+    for item in selector.split(',').map(str::trim) {
+        let (item_days, item_parts) = parse_item(year, item)?;
+        for day in item_days {
+            match days.iter_mut().find(|(d, _)| *d == day) {
+                Some((_, parts)) => {
+                    for part in &item_parts {
+                        if !parts.contains(part) {
+                            parts.push(*part);
+                        }
+                    }
+                }
+                None => days.push((day, item_parts.clone())),
+            }
+        }
+    }
+    days.sort_by_key(|(day, _)| *day);
+    days.iter_mut()
+        .for_each(|(_, parts)| parts.sort_by_key(Part::number));
+    Ok(days)
+}
+
+fn parse_item(year: u16, item: &str) -> Result<(Vec<u8>, Vec<Part>), SelectorError> {
+    match item.split_once(':') {
+        Some((days, part)) => Ok((
+            parse_days(year, days.trim())?,
+            vec![parse_part(part.trim())?],
+        )),
+        None => Ok((parse_days(year, item)?, Part::ALL.to_vec())),
+    }
+}
+
+fn parse_days(year: u16, days: &str) -> Result<Vec<u8>, SelectorError> {
+    if days == "all" {
+        return Ok(puzzles_of(year).map(|puzzle| puzzle.day()).collect());
+    }
+    match days.split_once('-') {
+        Some((start, end)) => {
+            let start = parse_day(start.trim())?;
+            let end = parse_day(end.trim())?;
+            if start > end {
+                Err(SelectorError::InvalidRange(days.into()))
+            } else {
+                Ok((start..=end).collect())
+            }
+        }
+        None => parse_day(days).map(|day| vec![day]),
+    }
+}
+
+fn parse_day(day: &str) -> Result<u8, SelectorError> {
+    day.parse()
+        .map_err(|_| SelectorError::InvalidDay(day.into()))
+}
+
+fn parse_part(part: &str) -> Result<Part, SelectorError> {
+    part.parse()
+        .ok()
+        .and_then(Part::from_number)
+        .ok_or_else(|| SelectorError::InvalidPart(part.into()))
+}
+
+#[cfg(test)]
+mod parse_selector_should {
+    use super::*;
+    use Part::{One, Two};
+
+    #[test]
+    fn return_both_parts_of_a_single_day() {
+        assert_eq!(parse_selector(2020, "14"), Ok(vec![(14, vec![One, Two])]));
+    }
+
+    #[test]
+    fn return_every_day_of_a_range() {
+        let days = parse_selector(2020, "1-4")
+            .unwrap()
+            .into_iter()
+            .map(|(day, _)| day)
+            .collect::<Vec<_>>();
+
+        assert_eq!(days, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn return_each_day_of_a_list_once_in_ascending_order() {
+        let days = parse_selector(2020, "3,7,14,3-4")
+            .unwrap()
+            .into_iter()
+            .map(|(day, _)| day)
+            .collect::<Vec<_>>();
+
+        assert_eq!(days, vec![3, 4, 7, 14]);
+    }
+
+    #[test]
+    fn return_all_registered_days() {
+        assert_eq!(parse_selector(2020, "all").map(|days| days.len()), Ok(17));
+    }
+
+    #[test]
+    fn return_a_single_part() {
+        assert_eq!(parse_selector(2020, "15:2"), Ok(vec![(15, vec![Two])]));
+    }
+
+    #[test]
+    fn return_a_single_part_of_a_range() {
+        assert_eq!(
+            parse_selector(2020, "1-2:1"),
+            Ok(vec![(1, vec![One]), (2, vec![One])])
+        );
+    }
+
+    #[test]
+    fn merge_parts_of_the_same_day() {
+        assert_eq!(
+            parse_selector(2020, "14:2,14:1"),
+            Ok(vec![(14, vec![One, Two])])
+        );
+    }
+
+    #[test]
+    fn reject_a_day_that_is_not_a_number() {
+        assert_eq!(
+            parse_selector(2020, "3,seven"),
+            Err(SelectorError::InvalidDay("seven".into()))
+        );
+    }
+
+    #[test]
+    fn reject_a_reversed_range() {
+        assert_eq!(
+            parse_selector(2020, "9-1"),
+            Err(SelectorError::InvalidRange("9-1".into()))
+        );
+    }
+
+    #[test]
+    fn reject_an_unknown_part() {
+        assert_eq!(
+            parse_selector(2020, "14:3"),
+            Err(SelectorError::InvalidPart("3".into()))
+        );
+    }
+}
+
+#[cfg(test)]
+mod select_puzzles_should {
+    use super::*;
+
+    #[test]
+    fn skip_days_without_a_puzzle() {
+        let days = select_puzzles(2020, "16-25")
+            .unwrap()
+            .iter()
+            .map(|selection| selection.puzzle.day())
+            .collect::<Vec<_>>();
+
+        assert_eq!(days, vec![16, 17]);
+    }
+
+    #[test]
+    fn fail_when_no_day_has_a_puzzle() {
+        assert_eq!(
+            select_puzzles(2020, "20-25").err(),
+            Some(SelectorError::NoMatchingDay(2020, "20-25".into()))
+        );
+    }
+
+    #[test]
+    fn fail_for_a_year_without_puzzles() {
+        assert_eq!(
+            select_puzzles(2016, "all").err(),
+            Some(SelectorError::NoMatchingDay(2016, "all".into()))
+        );
+    }
+}
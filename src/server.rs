@@ -0,0 +1,529 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::format::records;
+use advent2020::error::Error;
+use advent2020::puzzle::{find_puzzle, Part, Puzzle};
+
+const MAX_HEAD_SIZE: u64 = 8 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy)]
+pub struct Limits {
+    pub max_body_size: usize,
+    pub max_run_time: Duration,
+    /// Solvers still running after their request timed out keep their slot until they finish
+    pub max_running_solvers: usize,
+    pub max_connections: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_body_size: 1024 * 1024,
+            max_run_time: Duration::from_secs(30),
+            max_running_solvers: thread::available_parallelism().map_or(1, |n| n.get()),
+            max_connections: 64,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: String) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl ToString) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.to_string() }).to_string(),
+        }
+    }
+
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            self.status,
+            reason(self.status),
+            self.body.len(),
+            self.body
+        )?;
+        writer.flush()
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Unknown",
+    }
+}
+
+/// Answers `POST /YEAR/day/N/part/P` with the input as body, one thread per connection
+pub fn serve(listener: TcpListener, limits: Limits) -> io::Result<()> {
+    let running = Arc::new(AtomicUsize::new(0));
+    let connections = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let connection = match Slot::take(&connections, limits.max_connections) {
+            Some(connection) => connection,
+            None => {
+                if stream.set_write_timeout(Some(READ_TIMEOUT)).is_ok() {
+                    let response = Response::error(503, "Too many connections, try again later");
+                    let _ = response.write_to(&mut stream);
+                }
+                continue;
+            }
+        };
+        let running = running.clone();
+        thread::spawn(move || {
+            let _connection = connection;
+            handle_connection(stream, limits, &running)
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream, limits: Limits, running: &Arc<AtomicUsize>) {
+    let local = stream
+        .peer_addr()
+        .map(|address| address.ip().is_loopback())
+        .unwrap_or(false);
+    let response = if !local {
+        Response::error(403, "Only local clients are served")
+    } else if stream.set_read_timeout(Some(READ_TIMEOUT)).is_err() {
+        return;
+    } else {
+        match stream.try_clone() {
+            Ok(mut writer) => respond(&mut BufReader::new(&stream), &mut writer, limits, running),
+            Err(_) => return,
+        }
+    };
+    let _ = response.write_to(&mut stream);
+}
+
+fn respond(
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+    limits: Limits,
+    running: &Arc<AtomicUsize>,
+) -> Response {
+    let request = match read_request(reader, writer, limits) {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let (puzzle, part) = match route(&request.path) {
+        Some(route) => route,
+        None => return Response::error(404, "Expected /YEAR/day/N/part/P"),
+    };
+    if request.method != "POST" {
+        return Response::error(405, "Expected the input as the body of a POST");
+    }
+    solve(puzzle, part, request.body, limits, running)
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+fn read_request(
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+    limits: Limits,
+) -> Result<Request, Response> {
+    let mut remaining = MAX_HEAD_SIZE;
+    let request_line = read_head_line(reader, &mut remaining)?;
+    let mut words = request_line.split_whitespace();
+    let (method, path) = match (words.next(), words.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(Response::error(400, "Invalid request line")),
+    };
+    let mut content_length = None;
+    let mut expect_continue = false;
+    loop {
+        let line = read_head_line(reader, &mut remaining)?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| Response::error(400, "Invalid header"))?;
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => {
+                let length = value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| Response::error(400, "Invalid Content-Length"))?;
+                content_length = Some(length);
+            }
+            "transfer-encoding" => {
+                return Err(Response::error(411, "Chunked bodies are not supported"))
+            }
+            "expect" => expect_continue = value.trim().eq_ignore_ascii_case("100-continue"),
+            _ => {}
+        }
+    }
+    let content_length = match content_length {
+        Some(length) if length > limits.max_body_size => {
+            return Err(Response::error(
+                413,
+                format!("The input must not exceed {} bytes", limits.max_body_size),
+            ))
+        }
+        Some(length) => length,
+        None if method == "POST" => return Err(Response::error(411, "Missing Content-Length")),
+        None => 0,
+    };
+    if expect_continue {
+        writer
+            .write_all(b"HTTP/1.1 100 Continue\r\n\r\n")
+            .map_err(|_| Response::error(400, "Connection closed"))?;
+    }
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| Response::error(400, "Incomplete body"))?;
+    let body =
+        String::from_utf8(body).map_err(|_| Response::error(400, "The input must be UTF-8"))?;
+    Ok(Request { method, path, body })
+}
+
+fn read_head_line(reader: &mut impl BufRead, remaining: &mut u64) -> Result<String, Response> {
+    let mut line = String::new();
+    let read = reader
+        .take(*remaining)
+        .read_line(&mut line)
+        .map_err(|_| Response::error(400, "Unreadable request"))?;
+    *remaining -= read as u64;
+    if !line.ends_with('\n') {
+        return Err(if *remaining == 0 {
+            Response::error(431, "Request head too large")
+        } else {
+            Response::error(400, "Incomplete request")
+        });
+    }
+    Ok(line.trim_end().to_string())
+}
+
+fn route(path: &str) -> Option<(&'static dyn Puzzle, Part)> {
+    let path = path.split('?').next().unwrap_or_default();
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        ["", year, "day", day, "part", part] => {
+            let puzzle = find_puzzle(year.parse().ok()?, day.parse().ok()?)?;
+            let part = Part::from_number(part.parse().ok()?)?;
+            Some((puzzle, part))
+        }
+        _ => None,
+    }
+}
+
+/// Holds one of the slots of a counter until dropped
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn take(running: &Arc<AtomicUsize>, max_running_solvers: usize) -> Option<Self> {
+        let slot = Self(running.clone());
+        if running.fetch_add(1, Ordering::SeqCst) < max_running_solvers {
+            Some(slot)
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Runs the solver on its own thread, abandoning it after the maximum run time
+fn solve(
+    puzzle: &'static dyn Puzzle,
+    part: Part,
+    input: String,
+    limits: Limits,
+    running: &Arc<AtomicUsize>,
+) -> Response {
+    let slot = match Slot::take(running, limits.max_running_solvers) {
+        Some(slot) => slot,
+        None => return Response::error(503, "Too many running solvers, try again later"),
+    };
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        let _slot = slot;
+        let _ = sender.send(puzzle.execute(&input, &[part]));
+    });
+    match receiver.recv_timeout(limits.max_run_time) {
+        Ok(Ok(execution)) => Response::ok(
+            serde_json::to_string(&records(puzzle, &execution)[0])
+                .expect("Records are always serializable"),
+        ),
+        Ok(Err(error @ Error::InvalidInput { .. })) => Response::error(422, error),
+        Ok(Err(error)) => Response::error(404, error),
+        Err(RecvTimeoutError::Timeout) => Response::error(
+            504,
+            format!(
+                "Day {} part {} did not finish within {} s",
+                puzzle.day(),
+                part,
+                limits.max_run_time.as_secs_f64()
+            ),
+        ),
+        Err(RecvTimeoutError::Disconnected) => Response::error(500, "The solver panicked"),
+    }
+}
+
+#[cfg(test)]
+mod respond_should {
+    use super::*;
+    use std::io::Cursor;
+
+    fn respond_to(request: &str, limits: Limits) -> Response {
+        respond_counting(request, limits, &Arc::new(AtomicUsize::new(0)))
+    }
+
+    fn respond_counting(request: &str, limits: Limits, running: &Arc<AtomicUsize>) -> Response {
+        let mut written = Vec::new();
+        respond(
+            &mut Cursor::new(request.as_bytes()),
+            &mut written,
+            limits,
+            running,
+        )
+    }
+
+    fn post(path: &str, body: &str) -> String {
+        format!(
+            "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            path,
+            body.len(),
+            body
+        )
+    }
+
+    #[test]
+    fn return_the_answer_as_json() {
+        let response = respond_to(&post("/2020/day/15/part/1", "0,3,6"), Limits::default());
+
+        assert_eq!(response.status, 200);
+        assert!(response
+            .body
+            .starts_with(r#"{"year":2020,"day":15,"part":1,"label":"2020th round","answer":436,"#));
+    }
+
+    #[test]
+    fn reject_an_invalid_input() {
+        let response = respond_to(
+            &post("/2020/day/1/part/1", "1721\nabc\n"),
+            Limits::default(),
+        );
+
+        assert_eq!(
+            response,
+            Response::error(422, r#"Day 1, line 2: invalid number: "abc""#)
+        );
+    }
+
+    #[test]
+    fn reject_an_unknown_path() {
+        let response = respond_to(&post("/2020/day/26/part/1", "1"), Limits::default());
+
+        assert_eq!(response.status, 404);
+    }
+
+    #[test]
+    fn reject_a_year_without_puzzles() {
+        let response = respond_to(&post("/2016/day/1/part/1", "R2, L3"), Limits::default());
+
+        assert_eq!(response.status, 404);
+    }
+
+    #[test]
+    fn reject_another_method() {
+        let response = respond_to("GET /2020/day/1/part/1 HTTP/1.1\r\n\r\n", Limits::default());
+
+        assert_eq!(response.status, 405);
+    }
+
+    #[test]
+    fn reject_a_body_larger_than_the_limit() {
+        let limits = Limits {
+            max_body_size: 4,
+            ..Limits::default()
+        };
+
+        let response = respond_to(&post("/2020/day/15/part/1", "0,3,6"), limits);
+
+        assert_eq!(response.status, 413);
+    }
+
+    #[test]
+    fn reject_a_request_without_length() {
+        let response = respond_to(
+            "POST /2020/day/15/part/1 HTTP/1.1\r\n\r\n0,3,6",
+            Limits::default(),
+        );
+
+        assert_eq!(response.status, 411);
+    }
+
+    #[test]
+    fn give_up_on_a_solver_running_too_long() {
+        let limits = Limits {
+            max_run_time: Duration::from_millis(10),
+            ..Limits::default()
+        };
+
+        let response = respond_to(&post("/2020/day/15/part/2", "0,3,6"), limits);
+
+        assert_eq!(response.status, 504);
+    }
+
+    #[test]
+    fn keep_the_slot_of_a_solver_running_too_long_until_it_finishes() {
+        let limits = Limits {
+            max_run_time: Duration::from_millis(1),
+            max_running_solvers: 1,
+            ..Limits::default()
+        };
+        let running = Arc::new(AtomicUsize::new(0));
+
+        let timed_out =
+            respond_counting(&post("/2020/day/17/part/2", "##.\n.##"), limits, &running);
+        let rejected = respond_counting(&post("/2020/day/15/part/1", "0,3,6"), limits, &running);
+        while running.load(Ordering::SeqCst) > 0 {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let limits = Limits {
+            max_run_time: Duration::from_secs(30),
+            ..limits
+        };
+        let answered = respond_counting(&post("/2020/day/15/part/1", "0,3,6"), limits, &running);
+
+        assert_eq!(
+            (timed_out.status, rejected.status, answered.status),
+            (504, 503, 200)
+        );
+    }
+
+    #[test]
+    fn reject_a_solver_beyond_the_running_limit() {
+        let limits = Limits {
+            max_running_solvers: 0,
+            ..Limits::default()
+        };
+
+        let response = respond_to(&post("/2020/day/15/part/1", "0,3,6"), limits);
+
+        assert_eq!(response.status, 503);
+    }
+}
+
+#[cfg(test)]
+mod slot_should {
+    use super::*;
+
+    #[test]
+    fn not_be_taken_beyond_the_limit() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let slot = Slot::take(&running, 1);
+
+        assert!(slot.is_some());
+        assert!(Slot::take(&running, 1).is_none());
+        assert_eq!(running.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn be_freed_when_its_holder_panics() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let holder_running = running.clone();
+
+        let result = thread::spawn(move || {
+            let _slot = Slot::take(&holder_running, 1);
+            panic!("the solver failed");
+        })
+        .join();
+
+        assert!(result.is_err());
+        assert_eq!(running.load(Ordering::SeqCst), 0);
+    }
+}
+
+#[cfg(test)]
+mod serve_should {
+    use super::*;
+
+    #[test]
+    fn answer_a_local_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, Limits::default()));
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        let input = "1721\n979\n366\n299\n675\n1456";
+        write!(
+            stream,
+            "POST /2020/day/1/part/1 HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            input.len(),
+            input
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(r#""answer":514579"#));
+    }
+
+    #[test]
+    fn turn_away_a_connection_beyond_the_limit() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let limits = Limits {
+            max_connections: 1,
+            ..Limits::default()
+        };
+        thread::spawn(move || serve(listener, limits));
+
+        let _idle = TcpStream::connect(address).unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Alignment {
+    Left,
+    Right,
+}
+
+pub struct Table {
+    header: Vec<(String, Alignment)>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(header: &[(&str, Alignment)]) -> Self {
+        Self {
+            header: header
+                .iter()
+                .map(|(title, alignment)| (title.to_string(), *alignment))
+                .collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    fn widths(&self) -> Vec<usize> {
+        self.header
+            .iter()
+            .enumerate()
+            .map(|(i, (title, _))| {
+                self.rows
+                    .iter()
+                    .flat_map(|row| row.get(i))
+                    .chain(std::iter::once(title))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+
+    fn write_row<'a>(
+        &self,
+        f: &mut Formatter<'_>,
+        cells: impl Iterator<Item = &'a str>,
+        widths: &[usize],
+    ) -> std::fmt::Result {
+        let line = cells
+            .zip(widths)
+            .zip(self.header.iter().map(|(_, alignment)| alignment))
+            .map(|((cell, width), alignment)| match alignment {
+                Alignment::Left => format!("{:<width$}", cell, width = width),
+                Alignment::Right => format!("{:>width$}", cell, width = width),
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+        writeln!(f, "{}", line.trim_end())
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let widths = self.widths();
+        self.write_row(
+            f,
+            self.header.iter().map(|(title, _)| title.as_str()),
+            &widths,
+        )?;
+        writeln!(
+            f,
+            "{}",
+            widths
+                .iter()
+                .map(|width| "-".repeat(*width))
+                .collect::<Vec<_>>()
+                .join("-+-")
+        )?;
+        for row in &self.rows {
+            self.write_row(
+                f,
+                row.iter()
+                    .map(String::as_str)
+                    .chain(std::iter::repeat(""))
+                    .take(widths.len()),
+                &widths,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod table_should {
+    use super::*;
+
+    #[test]
+    fn align_cells_on_the_widest_value_of_each_column() {
+        let mut table = Table::new(&[("Day", Alignment::Right), ("Title", Alignment::Left)]);
+        table.push(vec!["1".into(), "Report Repair".into()]);
+        table.push(vec!["14".into(), "Docking Data".into()]);
+
+        assert_eq!(
+            table.to_string(),
+            "Day | Title\n\
+             ----+--------------\n  \
+             1 | Report Repair\n \
+             14 | Docking Data\n"
+        );
+    }
+}
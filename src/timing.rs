@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    if nanos < 1_000 {
+        format!("{} ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.1} µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.2} ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2} s", nanos as f64 / 1e9)
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Statistics {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Statistics {
+    pub fn of(durations: &[Duration]) -> Option<Self> {
+        let mut durations = durations.to_vec();
+        durations.sort_unstable();
+        let middle = durations.len() / 2;
+        let median = if durations.len().is_multiple_of(2) {
+            (*durations.get(middle.checked_sub(1)?)? + durations[middle]) / 2
+        } else {
+            durations[middle]
+        };
+        Some(Self {
+            min: *durations.first()?,
+            median,
+            max: *durations.last()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod format_duration_should {
+    use super::*;
+
+    #[test]
+    fn use_nanoseconds_below_a_microsecond() {
+        assert_eq!(format_duration(Duration::from_nanos(999)), "999 ns");
+    }
+
+    #[test]
+    fn use_microseconds_below_a_millisecond() {
+        assert_eq!(format_duration(Duration::from_nanos(12_345)), "12.3 µs");
+    }
+
+    #[test]
+    fn use_milliseconds_below_a_second() {
+        assert_eq!(format_duration(Duration::from_micros(1_500)), "1.50 ms");
+    }
+
+    #[test]
+    fn use_seconds_above_a_second() {
+        assert_eq!(format_duration(Duration::from_millis(2_345)), "2.35 s");
+    }
+}
+
+#[cfg(test)]
+mod statistics_should {
+    use super::*;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|v| Duration::from_millis(*v)).collect()
+    }
+
+    #[test]
+    fn be_none_without_durations() {
+        assert_eq!(Statistics::of(&[]), None);
+    }
+
+    #[test]
+    fn use_the_middle_value_as_median_for_an_odd_number_of_durations() {
+        assert_eq!(
+            Statistics::of(&millis(&[5, 1, 3])),
+            Some(Statistics {
+                min: Duration::from_millis(1),
+                median: Duration::from_millis(3),
+                max: Duration::from_millis(5),
+            })
+        );
+    }
+
+    #[test]
+    fn use_the_mean_of_the_middle_values_as_median_for_an_even_number_of_durations() {
+        assert_eq!(
+            Statistics::of(&millis(&[4, 1, 2, 8])).map(|s| s.median),
+            Some(Duration::from_millis(3))
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Intermediate steps of the solvers, written to the standard error. The `trace!` calls compile
+//! down to nothing unless the `trace` feature is enabled.
+
+use std::fmt::Arguments;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+pub const ENABLED: bool = cfg!(feature = "trace");
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Level {
+    /// `-v`
+    Info = 1,
+    /// `-vv`
+    Debug = 2,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_verbosity(verbosity: u8) {
+    VERBOSITY.store(verbosity, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= level as u8
+}
+
+pub fn emit(module: &str, arguments: Arguments) {
+    eprintln!("[{}] {}", source(module), arguments);
+}
+
+fn source(module: &str) -> &str {
+    module
+        .strip_prefix("advent2020::puzzle::")
+        .or_else(|| module.strip_prefix("advent2020::"))
+        .unwrap_or(module)
+}
+
+macro_rules! trace {
+    ($level:ident, $($argument:tt)*) => {
+        if $crate::trace::ENABLED && $crate::trace::enabled($crate::trace::Level::$level) {
+            $crate::trace::emit(module_path!(), format_args!($($argument)*));
+        }
+    };
+}
+
+pub(crate) use trace;
+
+#[cfg(test)]
+mod source_should {
+    use super::*;
+
+    #[test]
+    fn be_the_module_of_the_day() {
+        assert_eq!(source("advent2020::puzzle::day14::part2"), "day14::part2");
+    }
+
+    #[test]
+    fn be_the_module_of_a_shared_helper() {
+        assert_eq!(source("advent2020::math"), "math");
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs::metadata;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+type Stamp = (SystemTime, u64);
+
+/// Polls the modification time and size of a file
+pub struct Watcher {
+    path: PathBuf,
+    stamp: Option<Stamp>,
+}
+
+impl Watcher {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.into(),
+            stamp: stamp(path),
+        }
+    }
+
+    /// Blocks until the file changes then stays the same for a poll, ignoring the moments it
+    /// does not exist (e.g. while an editor replaces it)
+    pub fn wait(&mut self) {
+        let mut previous = self.stamp;
+        loop {
+            sleep(POLL_INTERVAL);
+            let stamp = stamp(&self.path);
+            if stamp.is_some() && stamp != self.stamp && stamp == previous {
+                self.stamp = stamp;
+                return;
+            }
+            previous = stamp;
+        }
+    }
+}
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let metadata = metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod watcher_should {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::{read_to_string, remove_file, write};
+    use std::process;
+    use std::thread;
+
+    #[test]
+    fn return_when_the_file_changes() {
+        let path = temp_dir().join(format!("advent2020-watch-{}", process::id()));
+        write(&path, "nop +0\n").unwrap();
+        let mut watcher = Watcher::new(&path);
+
+        let writer = {
+            let path = path.clone();
+            thread::spawn(move || {
+                sleep(POLL_INTERVAL * 2);
+                write(&path, "nop +0\nacc +1\n").unwrap();
+            })
+        };
+        watcher.wait();
+        let content = read_to_string(&path).unwrap();
+        writer.join().unwrap();
+        remove_file(&path).unwrap();
+
+        assert_eq!(content, "nop +0\nacc +1\n");
+    }
+}